@@ -1,16 +1,186 @@
 use schemars::JsonSchema;
 use serde::Serialize;
 
+use crate::error::CopilotError;
+
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct SymbolInfo {
     /// anchor text (symbol identifier)
     pub symbol_id: String,
     /// path/href to the symbol page from the crate docs (e.g., `macro.anyhow.html`)
     pub symbol_path: String,
-    /// type of symbol: module|macro|struct|enum|function|type_alias
+    /// Rust path of the symbol (e.g., `serde::de::Deserializer`)
+    pub fully_qualified_name: String,
+    /// type of symbol: module|macro|struct|enum|function|type_alias|trait|constant|static|union|
+    /// trait_alias|attribute_macro|derive_macro
     pub symbol_type: String,
     /// optional description (converted to markdown)
     pub symbol_description: Option<String>,
+    /// first line or sentence of `symbol_description`
+    pub summary: Option<String>,
+    /// listed with a `Deprecated` badge; `cargo_deprecated` has the version and note
+    pub deprecated: bool,
+    /// listed with an `Experimental` (unstable) badge
+    pub unstable: bool,
+    /// other Rust paths the same page is listed under, i.e. `pub use` re-exports
+    pub reexported_as: Vec<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ItemAttributes {
+    /// item the attributes belong to: the page's own symbol id or an anchor like `method.new`
+    pub item: String,
+    /// attributes as rendered by rustdoc without the `#[...]` wrapper, e.g. `must_use`, `repr(u8)`, `deprecated`
+    pub attributes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CfgVariant {
+    /// cfg condition as rendered by rustdoc (e.g. `Available on Unix only.`), none when ungated
+    pub cfg: Option<String>,
+    /// plain-text declaration of this variant
+    pub signature: String,
+    /// optional docs of this variant (converted to markdown)
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ItemVariants {
+    /// item the variants belong to: the page's own symbol id or an anchor like `method.new`
+    pub item: String,
+    pub variants: Vec<CfgVariant>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReexportInfo {
+    /// path the item is reachable at, e.g. `tokio::sync::Mutex` (ends in `*` for glob re-exports)
+    pub public_path: String,
+    /// fully qualified path of the defining item when rustdoc knows it, e.g. `tokio::sync::mutex::Mutex`
+    pub canonical_path: Option<String>,
+    /// kind of the re-exported item as rendered by rustdoc (struct|enum|fn|mod|...)
+    pub symbol_type: Option<String>,
+    /// doc page of the item relative to the crate docs, usable with `cargo_doc_get`
+    pub symbol_path: Option<String>,
+    /// the `pub use` declaration as written
+    pub declaration: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Diagnostic {
+    /// error|warning|note|help|...
+    pub level: String,
+    pub message: String,
+    /// lint or error code, e.g. `E0308` or `unused_variables`
+    pub code: Option<String>,
+    /// file of the primary span
+    pub file: Option<String>,
+    /// 1-based line of the primary span
+    pub line: Option<usize>,
+    /// 1-based column of the primary span
+    pub column: Option<usize>,
+    /// full human-readable rendering as printed by rustc
+    pub rendered: Option<String>,
+    /// code replacements proposed by the compiler or lint
+    pub suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Suggestion {
+    /// help message the replacement belongs to
+    pub message: String,
+    pub file: String,
+    /// 1-based line where the replaced span starts
+    pub line: usize,
+    /// 1-based column where the replaced span starts
+    pub column: usize,
+    /// 1-based line where the replaced span ends
+    pub end_line: usize,
+    /// 1-based column just past the replaced span
+    pub end_column: usize,
+    /// text that replaces the span
+    pub replacement: String,
+    /// MachineApplicable|HasPlaceholders|MaybeIncorrect|Unspecified; MachineApplicable
+    /// replacements are safe to apply as is
+    pub applicability: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DeprecationInfo {
+    /// doc page the deprecated item is documented on
+    pub symbol_path: String,
+    /// anchor of a deprecated method on that page (e.g. `method.new`), none for the page's own item
+    pub anchor: Option<String>,
+    /// version from `#[deprecated(since = "...")]`
+    pub since: Option<String>,
+    /// note from `#[deprecated(note = "...")]` (markdown)
+    pub note: Option<String>,
+    /// suggested alternative when the note names one, e.g. `use `new` instead` -> `new`
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct StructField {
+    /// field name, the position (`0`, `1`, ...) for tuple structs
+    pub name: String,
+    /// field type as rendered by rustdoc, e.g. `Vec<T>`
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// field docs (markdown)
+    pub docs: Option<String>,
+    /// `pub`, `pub(crate)`, ... from the declaration, `private` without a modifier
+    pub visibility: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VariantInfo {
+    pub name: String,
+    /// unit|tuple|struct
+    pub kind: String,
+    /// explicit discriminant, e.g. `3` for `Foo = 3`
+    pub discriminant: Option<String>,
+    /// data of tuple (positional names) and struct variants, empty for unit variants
+    pub fields: Vec<StructField>,
+    /// variant docs (markdown)
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TraitImpl {
+    /// trait name as linked in the header, e.g. `Clone`
+    pub trait_name: String,
+    /// Rust path of the trait, e.g. `core::clone::Clone`
+    pub fully_qualified_name: Option<String>,
+    /// trait page relative to the crate docs, none for traits documented elsewhere (e.g. std)
+    pub symbol_path: Option<String>,
+    /// `impl` header as rendered by rustdoc, e.g. `impl Clone for Foo`
+    pub header: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MethodInfo {
+    pub name: String,
+    /// signature as rendered by rustdoc, e.g. `pub const fn new() -> Self`
+    pub signature: String,
+    /// first paragraph of the method docs (markdown)
+    pub summary: Option<String>,
+    pub is_async: bool,
+    pub is_unsafe: bool,
+    pub is_const: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ImplMethods {
+    /// `impl` header of the block, e.g. `impl<T: Clone> Foo<T>`
+    pub header: String,
+    pub methods: Vec<MethodInfo>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ExampleSection {
+    /// anchor of the documented member, e.g. `method.new`; absent for the item's own docs
+    pub item: Option<String>,
+    /// section content with code blocks fenced and labeled with their language
+    pub markdown: String,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -23,40 +193,495 @@ pub struct CrateInfo {
     pub crate_version: String,
     /// optional package description from Cargo.toml
     pub crate_description: Option<String>,
+    /// SPDX license expression, or the `license-file` path when only that is declared
+    pub license: Option<String>,
+    /// where the package comes from, absent when it isn't in the metadata
+    pub source: Option<DependencySource>,
+}
+
+/// Origin of a package, from `source` in `cargo metadata`
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum DependencySource {
+    /// the default crates.io index
+    CratesIo,
+    /// another registry
+    Registry { index: String },
+    Git {
+        url: String,
+        /// `branch=..`, `tag=..` or `rev=..` from the manifest
+        reference: Option<String>,
+        /// locked commit
+        commit: Option<String>,
+    },
+    /// local directory of the package
+    Path { path: String },
+}
+
+/// A direct dependency of a package
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DependencyInfo {
+    #[serde(flatten)]
+    pub info: CrateInfo,
+    /// how the package depends on it, any of normal|dev|build
+    pub kinds: Vec<String>,
+    /// only pulled in when a feature enables it
+    pub optional: bool,
+    /// features of the package that enable an optional dependency
+    pub enabled_by: Vec<String>,
+    /// name the dependency is renamed to in the manifest (`foo = { package = "real-foo" }`),
+    /// `crate_name` is the real package name
+    pub rename: Option<String>,
+}
+
+/// `package.license`, falling back to `package.license-file`
+pub fn package_license(pkg: &cargo_metadata::Package) -> Option<String> {
+    pkg.license
+        .clone()
+        .or_else(|| pkg.license_file.as_ref().map(|file| file.to_string()))
+}
+
+/// `source` of packages from crates.io, as reported by `cargo metadata`
+pub const CRATES_IO_SOURCES: &[&str] = &[
+    "registry+https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io/",
+];
+
+/// Where a resolved package comes from; packages without a source are local
+pub fn package_source(pkg: &cargo_metadata::Package) -> DependencySource {
+    match &pkg.source {
+        Some(source) => parse_source(&source.repr),
+        None => DependencySource::Path {
+            path: pkg
+                .manifest_path
+                .parent()
+                .map_or_else(|| pkg.manifest_path.to_string(), |dir| dir.to_string()),
+        },
+    }
+}
+
+/// Where a declared dependency comes from, `None` for a path dependency without its `path`
+pub fn dependency_source(dep: &cargo_metadata::Dependency) -> Option<DependencySource> {
+    match (&dep.source, &dep.path) {
+        (Some(source), _) => Some(parse_source(source)),
+        (None, Some(path)) => Some(DependencySource::Path {
+            path: path.to_string(),
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Classify a source id: `registry+<index>`, `sparse+<index>` or `git+<url>[?<ref>][#<commit>]`
+fn parse_source(repr: &str) -> DependencySource {
+    if CRATES_IO_SOURCES.contains(&repr) {
+        return DependencySource::CratesIo;
+    }
+    if let Some(rest) = repr.strip_prefix("git+") {
+        let (rest, commit) = match rest.split_once('#') {
+            Some((rest, commit)) => (rest, Some(commit.to_string())),
+            None => (rest, None),
+        };
+        let (url, reference) = match rest.split_once('?') {
+            Some((url, reference)) => (url, Some(reference.to_string())),
+            None => (rest, None),
+        };
+        return DependencySource::Git {
+            url: url.to_string(),
+            reference,
+            commit,
+        };
+    }
+    let index = repr
+        .strip_prefix("registry+")
+        .or_else(|| repr.strip_prefix("sparse+"))
+        .unwrap_or(repr);
+    DependencySource::Registry {
+        index: index.to_string(),
+    }
 }
 
-/// Fetch cargo metadata in a blocking task and convert errors to String for the tool API
-pub async fn get_metadata() -> Result<cargo_metadata::Metadata, String> {
-    tokio::task::spawn_blocking(|| cargo_metadata::MetadataCommand::new().exec())
+/// Cargo binary to run: `CARGO` as set by cargo for subcommands, otherwise `cargo` from `PATH`
+pub fn cargo_bin() -> std::ffi::OsString {
+    std::env::var_os("CARGO")
+        .filter(|cargo| !cargo.is_empty())
+        .unwrap_or_else(|| "cargo".into())
+}
+
+static MANIFEST_PATH: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+/// Point every cargo invocation at this `Cargo.toml` instead of the one found from the current
+/// directory. Only the first call has an effect.
+pub fn set_manifest_path(path: std::path::PathBuf) {
+    let _ = MANIFEST_PATH.set(path);
+}
+
+static OFFLINE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Turn on offline mode regardless of `CARGO_COPILOT_OFFLINE`
+pub fn set_offline() {
+    OFFLINE.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Whether cargo may only use crates already in the local registry cache: `--offline` was
+/// passed or `CARGO_COPILOT_OFFLINE` is set
+pub fn offline() -> bool {
+    OFFLINE.load(std::sync::atomic::Ordering::Relaxed) || env_flag("CARGO_COPILOT_OFFLINE")
+}
+
+/// `cargo` command running in the directory of the configured manifest, if any, and in its own
+/// process group so [`ProcessGroupGuard`] can stop the compilers it starts
+pub fn cargo_command() -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new(cargo_bin());
+    if let Some(dir) = MANIFEST_PATH.get().and_then(|path| path.parent()) {
+        cmd.current_dir(dir);
+    }
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd
+}
+
+/// Kills the process group of a child started by [`cargo_command`] when dropped before
+/// [`disarm`](Self::disarm) is called, e.g. on a timeout or a cancelled request. `kill_on_drop`
+/// only reaches cargo itself, leaving the rustc and rustdoc processes it spawned running.
+pub struct ProcessGroupGuard(Option<u32>);
+
+/// Process groups of the guarded children still running, killed by [`kill_running`]
+static RUNNING_GROUPS: std::sync::LazyLock<std::sync::Mutex<std::collections::HashSet<u32>>> =
+    std::sync::LazyLock::new(Default::default);
+
+impl ProcessGroupGuard {
+    pub fn new(child: &tokio::process::Child) -> Self {
+        let pid = child.id();
+        if let Some(pid) = pid {
+            RUNNING_GROUPS.lock().unwrap().insert(pid);
+        }
+        Self(pid)
+    }
+
+    /// The child exited on its own, nothing is left to kill
+    pub fn disarm(&mut self) {
+        if let Some(pid) = self.0.take() {
+            RUNNING_GROUPS.lock().unwrap().remove(&pid);
+        }
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        if let Some(pid) = self.0.take() {
+            RUNNING_GROUPS.lock().unwrap().remove(&pid);
+            kill_group(pid);
+        }
+    }
+}
+
+/// Kill every guarded process group still running, leaving their callers to report the failure
+pub fn kill_running() -> usize {
+    let groups: Vec<u32> = RUNNING_GROUPS.lock().unwrap().drain().collect();
+    for &pid in &groups {
+        kill_group(pid);
+    }
+    groups.len()
+}
+
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn kill_group(pid: u32) {
+    #[cfg(unix)]
+    if let Ok(pgid) = i32::try_from(pid) {
+        // SAFETY: kill(2) has no memory safety preconditions
+        unsafe {
+            libc::kill(-pgid, libc::SIGKILL);
+        }
+    }
+}
+
+/// Like `Command::output`, killing the child's whole process group if the future is dropped
+pub async fn output_in_group(
+    cmd: &mut tokio::process::Command,
+) -> std::io::Result<std::process::Output> {
+    let child = cmd
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut group = ProcessGroupGuard::new(&child);
+    let output = child.wait_with_output().await;
+    group.disarm();
+    output
+}
+
+/// Fetch cargo metadata in a blocking task, `--offline` in [`offline`] mode
+pub async fn get_metadata() -> Result<cargo_metadata::Metadata, CopilotError> {
+    let mut cmd = cargo_metadata::MetadataCommand::new();
+    cmd.cargo_path(cargo_bin());
+    if let Some(path) = MANIFEST_PATH.get() {
+        cmd.manifest_path(path);
+    }
+    if offline() {
+        cmd.other_options(vec!["--offline".to_string()]);
+    }
+    Ok(tokio::task::spawn_blocking(move || cmd.exec()).await??)
+}
+
+/// Run synchronous HTML parsing on the blocking pool so large pages don't stall the runtime
+pub async fn parse_blocking<T, F>(f: F) -> Result<T, CopilotError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    Ok(tokio::task::spawn_blocking(f).await?)
+}
+
+/// Pick a workspace member by `name` or `name@version`, or the root package when none is named
+pub fn find_workspace_member<'a>(
+    metadata: &'a cargo_metadata::Metadata,
+    package: Option<&str>,
+) -> Result<&'a cargo_metadata::Package, CopilotError> {
+    let members = metadata.workspace_packages();
+    let member_ids = || {
+        let mut ids: Vec<String> = members
+            .iter()
+            .map(|pkg| format!("{}@{}", pkg.name, pkg.version))
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    let Some(package) = package else {
+        return metadata
+            .root_package()
+            .ok_or_else(|| CopilotError::WorkspaceMemberRequired {
+                members: member_ids(),
+            });
+    };
+    members
+        .iter()
+        .copied()
+        .find(|pkg| pkg.name == package || format!("{}@{}", pkg.name, pkg.version) == package)
+        .ok_or_else(|| CopilotError::NotWorkspaceMember {
+            package: package.to_string(),
+            members: member_ids(),
+        })
+}
+
+/// `--package <spec>` for a workspace member, checked against the metadata first so a typo fails
+/// fast with the member list instead of after a cargo run, or `--workspace` when none is named
+pub async fn package_args(package: Option<&str>) -> Result<Vec<String>, CopilotError> {
+    let Some(package) = package else {
+        return Ok(vec!["--workspace".to_string()]);
+    };
+    let metadata = get_metadata().await?;
+    let pkg = find_workspace_member(&metadata, Some(package))?;
+    Ok(vec!["--package".to_string(), package_spec(pkg)])
+}
+
+/// Lines a `cargo add` or `cargo remove` run changed in a manifest
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ManifestChange {
+    /// `Cargo.toml` that was edited
+    pub manifest_path: String,
+    /// lines of the new manifest that weren't in the old one
+    pub added: Vec<String>,
+    /// lines of the old manifest that are gone
+    pub removed: Vec<String>,
+    /// cargo's own report, e.g. `Adding memchr v2.7.4 to dependencies` and the features list
+    pub cargo_output: String,
+}
+
+/// Run `cargo <subcommand>` (`add` or `remove`) with `args` against a workspace member, or the
+/// root package, and diff its manifest. Cargo failing is [`CopilotError::CargoEditFailed`] with
+/// its stderr.
+pub async fn edit_manifest(
+    subcommand: &'static str,
+    args: &[String],
+    package: Option<&str>,
+) -> Result<ManifestChange, CopilotError> {
+    let metadata = get_metadata().await?;
+    let pkg = find_workspace_member(&metadata, package)?;
+    let manifest_path = pkg.manifest_path.clone().into_std_path_buf();
+    let read = |path: &std::path::Path| {
+        std::fs::read_to_string(path).map_err(|e| CopilotError::io("read", path, e))
+    };
+    let before = read(&manifest_path)?;
+
+    let mut cmd = cargo_command();
+    cmd.arg(subcommand)
+        .args(args)
+        .arg("--package")
+        .arg(package_spec(pkg));
+    if offline() {
+        cmd.arg("--offline");
+    }
+    let output = output_in_group(&mut cmd)
         .await
-        .map_err(|e| format!("failed to run cargo metadata task: {}", e))?
-        .map_err(|e| format!("cargo metadata error: {}", e))
+        .map_err(|source| CopilotError::Spawn {
+            command: "cargo",
+            source,
+        })?;
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() {
+        return Err(CopilotError::CargoEditFailed {
+            subcommand,
+            status: output.status,
+            stderr,
+        });
+    }
+
+    let after = read(&manifest_path)?;
+    let (added, removed) = line_diff(&before, &after);
+    tracing::info!(
+        manifest = %manifest_path.display(),
+        args = ?args,
+        "cargo {} modified the manifest",
+        subcommand
+    );
+    Ok(ManifestChange {
+        manifest_path: manifest_path.display().to_string(),
+        added,
+        removed,
+        cargo_output: stderr,
+    })
+}
+
+/// `(added, removed)` non-blank lines between two texts, from their longest common subsequence
+fn line_diff(before: &str, after: &str) -> (Vec<String>, Vec<String>) {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            added.push(new[j].to_string());
+            j += 1;
+        } else {
+            removed.push(old[i].to_string());
+            i += 1;
+        }
+    }
+    added.retain(|line| !line.trim().is_empty());
+    removed.retain(|line| !line.trim().is_empty());
+    (added, removed)
+}
+
+/// Find the package matching a `name@version` or bare `name` crate id.
+///
+/// A bare name is an error when the workspace resolves several versions of that package, since
+/// documenting either one would be a guess.
+pub fn find_package<'a>(
+    metadata: &'a cargo_metadata::Metadata,
+    crate_id: &str,
+) -> Result<&'a cargo_metadata::Package, CopilotError> {
+    let (name, version) = match crate_id.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (crate_id, None),
+    };
+
+    if version.is_none() {
+        let mut candidates: Vec<&cargo_metadata::Package> = metadata
+            .packages
+            .iter()
+            .filter(|p| p.name == name)
+            .collect();
+        candidates.sort_by(|a, b| a.version.cmp(&b.version));
+        candidates.dedup_by(|a, b| a.version == b.version);
+        if candidates.len() > 1 {
+            return Err(CopilotError::AmbiguousPackage {
+                name: name.to_string(),
+                candidates: candidates
+                    .iter()
+                    .map(|p| format!("{}@{}", p.name, p.version))
+                    .collect(),
+            });
+        }
+    }
+
+    metadata
+        .packages
+        .iter()
+        .find(|p| p.name == name && version.is_none_or(|v| p.version.to_string() == v))
+        .ok_or_else(|| CopilotError::PackageNotFound {
+            crate_id: crate_id.to_string(),
+            suggestions: suggest_crate_ids(metadata, name),
+        })
+}
+
+/// Up to three `name@version` ids of packages whose name is closest to `name`, the standard
+/// library crates the doc tools read from the toolchain included as bare names
+pub fn suggest_crate_ids(metadata: &cargo_metadata::Metadata, name: &str) -> Vec<String> {
+    let name = name.to_lowercase().replace('_', "-");
+    let packages = metadata
+        .packages
+        .iter()
+        .map(|p| (p.name.as_str(), format!("{}@{}", p.name, p.version)));
+    let std_crates = crate::sysroot::STD_CRATES
+        .iter()
+        .map(|&std_crate| (std_crate, std_crate.to_string()));
+    let mut scored: Vec<(f64, String)> = packages
+        .chain(std_crates)
+        .map(|(candidate, id)| {
+            let score = strsim::jaro_winkler(&name, &candidate.to_lowercase().replace('_', "-"));
+            (score, id)
+        })
+        .filter(|(score, _)| *score >= 0.8)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored.into_iter().take(3).map(|(_, id)| id).collect()
 }
 
 /// Collect crate info objects in a deterministic and readable way
 pub fn get_dependencies(
     metadata: &cargo_metadata::Metadata,
     root: &cargo_metadata::Package,
-) -> Vec<CrateInfo> {
+) -> Vec<DependencyInfo> {
     // Try to use the resolved dependency graph when available (gives exact package info)
     if let Some(node) = find_root_resolve_node(metadata, root) {
-        let infos = resolved_dep_infos(node, metadata);
+        let mut infos = resolved_dep_infos(node, metadata);
+        // the resolve graph doesn't record optionality or renames, the manifest does
+        for dep in &mut infos {
+            let declared = declarations(root, &dep.info.crate_name, &dep.info.crate_version);
+            dep.optional = !declared.is_empty() && declared.iter().all(|d| d.optional);
+            dep.enabled_by = enabling_features(root, &declared);
+            dep.rename = declared.iter().find_map(|d| d.rename.clone());
+        }
         if !infos.is_empty() {
             return unique_sorted_crates(infos);
         }
     }
 
     // Fallback: use declared dependencies and look up package info from `metadata.packages`
-    let infos: Vec<CrateInfo> = root
+    let infos: Vec<DependencyInfo> = root
         .dependencies
         .iter()
         .map(|d| {
-            if let Some(p) = metadata.packages.iter().find(|p| p.name == d.name) {
+            // `name` is the real package name even when the dependency is renamed; prefer the
+            // version the requirement accepts when several are in the graph
+            let by_name = || metadata.packages.iter().filter(|p| p.name == d.name);
+            let pkg = by_name()
+                .find(|p| d.req.matches(&p.version))
+                .or_else(|| by_name().next());
+            let info = if let Some(p) = pkg {
                 CrateInfo {
                     crate_id: format!("{}@{}", p.name, p.version),
                     crate_name: p.name.clone(),
                     crate_version: p.version.to_string(),
                     crate_description: p.description.clone(),
+                    license: package_license(p),
+                    source: Some(package_source(p)),
                 }
             } else {
                 CrateInfo {
@@ -64,7 +689,16 @@ pub fn get_dependencies(
                     crate_name: d.name.clone(),
                     crate_version: String::new(),
                     crate_description: None,
+                    license: None,
+                    source: dependency_source(d),
                 }
+            };
+            DependencyInfo {
+                info,
+                kinds: dependency_kinds([d.kind]),
+                optional: d.optional,
+                enabled_by: enabling_features(root, &[d]),
+                rename: d.rename.clone(),
             }
         })
         .collect();
@@ -72,6 +706,44 @@ pub fn get_dependencies(
     unique_sorted_crates(infos)
 }
 
+/// Resolve graph nodes by package id, empty when `cargo metadata` didn't resolve
+pub fn resolve_nodes(
+    metadata: &cargo_metadata::Metadata,
+) -> std::collections::HashMap<&cargo_metadata::PackageId, &cargo_metadata::Node> {
+    metadata
+        .resolve
+        .iter()
+        .flat_map(|r| &r.nodes)
+        .map(|n| (&n.id, n))
+        .collect()
+}
+
+/// Packages reachable from `roots` through the resolve graph, the roots included
+pub fn reachable<'a>(
+    roots: impl IntoIterator<Item = &'a cargo_metadata::PackageId>,
+    nodes: &std::collections::HashMap<&'a cargo_metadata::PackageId, &'a cargo_metadata::Node>,
+) -> std::collections::HashSet<&'a cargo_metadata::PackageId> {
+    let mut pending: Vec<&cargo_metadata::PackageId> = roots.into_iter().collect();
+    let mut seen: std::collections::HashSet<&cargo_metadata::PackageId> =
+        pending.iter().copied().collect();
+    while let Some(id) = pending.pop() {
+        for dep in nodes.get(id).into_iter().flat_map(|n| &n.deps) {
+            if seen.insert(&dep.pkg) {
+                pending.push(&dep.pkg);
+            }
+        }
+    }
+    seen
+}
+
+/// Starting points of the graph walks: the root package, or every member of a virtual workspace
+pub fn graph_roots(metadata: &cargo_metadata::Metadata) -> Vec<&cargo_metadata::PackageId> {
+    match metadata.root_package() {
+        Some(root) => vec![&root.id],
+        None => metadata.workspace_members.iter().collect(),
+    }
+}
+
 /// Return the resolve node for the root package if available
 pub fn find_root_resolve_node<'a>(
     metadata: &'a cargo_metadata::Metadata,
@@ -87,13 +759,59 @@ pub fn find_root_resolve_node<'a>(
 pub fn resolved_dep_infos(
     node: &cargo_metadata::Node,
     metadata: &cargo_metadata::Metadata,
-) -> Vec<CrateInfo> {
+) -> Vec<DependencyInfo> {
     node.deps
         .iter()
-        .map(|d| format_dep_info(d, metadata))
+        .map(|d| DependencyInfo {
+            info: format_dep_info(d, metadata),
+            kinds: dependency_kinds(d.dep_kinds.iter().map(|k| k.kind)),
+            optional: false,
+            enabled_by: Vec::new(),
+            rename: None,
+        })
         .collect()
 }
 
+/// Declarations of the `name` package in `root`'s manifest whose requirement accepts `version`,
+/// so two versions renamed apart keep their own entries. Falls back to every declaration of the
+/// name when none does (a prerelease, or an unparsable version).
+fn declarations<'a>(
+    root: &'a cargo_metadata::Package,
+    name: &str,
+    version: &str,
+) -> Vec<&'a cargo_metadata::Dependency> {
+    let by_name = root.dependencies.iter().filter(|d| d.name == name);
+    let matching: Vec<_> = match cargo_metadata::semver::Version::parse(version) {
+        Ok(version) => by_name
+            .clone()
+            .filter(|d| d.req.matches(&version))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    if matching.is_empty() {
+        by_name.collect()
+    } else {
+        matching
+    }
+}
+
+/// Sorted, deduplicated `normal`/`dev`/`build` names of dependency kinds
+pub fn dependency_kinds(
+    kinds: impl IntoIterator<Item = cargo_metadata::DependencyKind>,
+) -> Vec<String> {
+    let mut names: Vec<String> = kinds
+        .into_iter()
+        .map(|kind| match kind {
+            cargo_metadata::DependencyKind::Development => "dev".to_string(),
+            cargo_metadata::DependencyKind::Build => "build".to_string(),
+            _ => "normal".to_string(),
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
 /// Format a NodeDep into a CrateInfo when possible, with fallbacks
 pub fn format_dep_info(
     dep: &cargo_metadata::NodeDep,
@@ -105,6 +823,8 @@ pub fn format_dep_info(
             crate_name: pkg.name.clone(),
             crate_version: pkg.version.to_string(),
             crate_description: pkg.description.clone(),
+            license: package_license(pkg),
+            source: Some(package_source(pkg)),
         }
     } else if let Some(pkg_by_name) = metadata.packages.iter().find(|p| p.name == dep.name) {
         CrateInfo {
@@ -112,6 +832,8 @@ pub fn format_dep_info(
             crate_name: pkg_by_name.name.clone(),
             crate_version: pkg_by_name.version.to_string(),
             crate_description: pkg_by_name.description.clone(),
+            license: package_license(pkg_by_name),
+            source: Some(package_source(pkg_by_name)),
         }
     } else {
         CrateInfo {
@@ -119,184 +841,1288 @@ pub fn format_dep_info(
             crate_name: dep.name.clone(),
             crate_version: String::new(),
             crate_description: None,
+            license: None,
+            source: None,
         }
     }
 }
 
-pub fn unique_sorted_crates(mut infos: Vec<CrateInfo>) -> Vec<CrateInfo> {
-    infos.sort_by(|a, b| a.crate_id.cmp(&b.crate_id));
-    infos.dedup_by(|a, b| a.crate_id == b.crate_id);
+/// Features of `root` turning on the optional ones of the `declared` dependencies: those listing
+/// `dep:<dep>`, `<dep>/<feature>` or a bare `<dep>`, where `<dep>` is the possibly renamed
+/// dependency. `<dep>?/<feature>` only applies when something else enables it, so it doesn't
+/// count.
+fn enabling_features(
+    root: &cargo_metadata::Package,
+    declared: &[&cargo_metadata::Dependency],
+) -> Vec<String> {
+    let deps: Vec<&str> = declared
+        .iter()
+        .filter(|d| d.optional)
+        .map(|d| d.rename.as_deref().unwrap_or(&d.name))
+        .collect();
+    let enables = |value: &str| {
+        let value = value.strip_prefix("dep:").unwrap_or(value);
+        let dep = value.split_once('/').map_or(value, |(dep, _)| dep);
+        deps.contains(&dep)
+    };
+    root.features
+        .iter()
+        .filter(|(_, values)| values.iter().any(|value| enables(value)))
+        .map(|(feature, _)| feature.clone())
+        .collect()
+}
+
+/// Sort dependencies by crate id, merging duplicates: kinds add up and a dependency stays
+/// optional only if it is optional everywhere
+pub fn unique_sorted_crates(mut infos: Vec<DependencyInfo>) -> Vec<DependencyInfo> {
+    infos.sort_by(|a, b| a.info.crate_id.cmp(&b.info.crate_id));
+    infos.dedup_by(|dup, kept| {
+        if dup.info.crate_id != kept.info.crate_id {
+            return false;
+        }
+        kept.kinds.append(&mut dup.kinds);
+        kept.kinds.sort();
+        kept.kinds.dedup();
+        kept.optional &= dup.optional;
+        kept.enabled_by.append(&mut dup.enabled_by);
+        kept.enabled_by.sort();
+        kept.enabled_by.dedup();
+        kept.rename = kept.rename.take().or(dup.rename.take());
+        true
+    });
     infos
 }
 
-/// Run `cargo doc --package <crate> --no-deps` to generate stable HTML docs
-pub async fn doc(crate_name: &str) -> Result<(), String> {
-    let status = tokio::process::Command::new("cargo")
-        .arg("doc")
-        .arg("--package")
-        .arg(crate_name)
-        .arg("--no-deps")
-        .status()
-        .await
-        .map_err(|e| format!("failed to spawn cargo doc: {}", e))?;
+/// Extra knobs for the `cargo doc` invocation
+#[derive(Debug, Default, Clone)]
+pub struct DocOptions {
+    /// add `--cfg docsrs` to `RUSTDOCFLAGS` so `doc(cfg(...))` annotations are rendered
+    pub docsrs: bool,
+    /// pass `--document-private-items` so non-`pub` items are documented too
+    pub document_private_items: bool,
+    /// features of the package to enable
+    pub features: Vec<String>,
+    /// enable every feature of the package
+    pub all_features: bool,
+}
 
-    if !status.success() {
-        return Err(format!(
-            "cargo doc failed with status: {}. Ensure the package exists locally",
-            status
-        ));
+impl DocOptions {
+    /// No option set, what `cargo doc` builds without flags
+    pub fn is_default(&self) -> bool {
+        !self.docsrs
+            && !self.document_private_items
+            && self.features.is_empty()
+            && !self.all_features
     }
+}
 
-    Ok(())
+// Doc build knobs shared by the tool requests that read generated docs. A plain comment, a doc
+// comment would become the description of every request schema it is flattened into.
+#[derive(Debug, Default, serde::Deserialize, JsonSchema)]
+pub struct DocBuildArgs {
+    /// also document non-`pub` items (`--document-private-items`)
+    #[serde(default)]
+    pub include_private: bool,
+    /// features of the crate to enable, e.g. `["tokio"]`, so feature-gated items are documented
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// enable every feature of the crate
+    #[serde(default)]
+    pub all_features: bool,
+    /// target triple to document for (`--target`), e.g. `x86_64-pc-windows-msvc` for items only
+    /// available there; the host otherwise. The rustup target must be installed.
+    pub target: Option<String>,
 }
 
-/// Read `target/doc/<crate>/index.html`
-pub async fn read_doc_index_html(crate_name: &str) -> Result<String, String> {
-    let path = std::path::Path::new("target")
-        .join("doc")
-        .join(crate_name)
-        .join("index.html");
-    let contents = tokio::fs::read_to_string(&path)
-        .await
-        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
-    Ok(contents)
+impl DocBuildArgs {
+    pub fn doc_options(&self) -> DocOptions {
+        DocOptions {
+            document_private_items: self.include_private,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            ..Default::default()
+        }
+    }
 }
 
-/// Read an arbitrary doc HTML file relative to the crate doc dir, e.g., "de/index.html" or "struct.Error.html"
-pub async fn read_doc_html_by_rel_path(crate_name: &str, rel_path: &str) -> Result<String, String> {
-    let path = std::path::Path::new("target")
-        .join("doc")
-        .join(crate_name)
-        .join(rel_path);
-    let contents = tokio::fs::read_to_string(&path)
-        .await
-        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
-    Ok(contents)
+/// How features of a package can be enabled from the command line: cargo only accepts
+/// `--features` for workspace members, dependencies need `<dependency>/<feature>` on a member
+#[derive(Debug, Clone)]
+pub enum FeatureScope {
+    Member,
+    Dependency {
+        /// package spec of a workspace member depending on the package directly
+        member: String,
+        /// doc directory name of that member, documented alongside the package
+        member_doc_name: String,
+        /// name (or rename) of the dependency in that member's manifest
+        dependency: String,
+        /// every feature of the package, for `all_features`
+        all: Vec<String>,
+    },
+    /// transitive dependency, features are passed as is and cargo reports the error
+    Unreachable,
 }
 
-/// Extract inner HTML of the first `div.docblock` in the page
-pub fn extract_docblock(html: &str) -> Option<String> {
-    let document = scraper::Html::parse_document(html);
-    let selector = match scraper::Selector::parse("div.docblock") {
-        Ok(s) => s,
-        Err(_) => return None,
-    };
-    let mut iter = document.select(&selector);
-    iter.next().map(|el| el.inner_html())
+/// A package resolved from a crate id, with what's needed to build and read its docs
+#[derive(Debug, Clone)]
+pub struct DocTarget {
+    /// `name@version` of the resolved package
+    pub crate_id: String,
+    /// version of the resolved package
+    pub version: String,
+    /// package id spec passed to `cargo doc --package`, unambiguous for git and path packages
+    pub spec: String,
+    /// directory of the generated docs under `doc_root`
+    pub doc_name: String,
+    /// `<target dir>/doc`, or `<target dir>/<triple>/doc` with a `triple`, where rustdoc writes
+    /// the docs of every package
+    pub doc_root: std::path::PathBuf,
+    /// target triple passed to `cargo doc --target`, the host when `None`
+    pub triple: Option<String>,
+    /// where the package comes from, see [`describe_source`]
+    pub source: String,
+    /// directory containing the package's `Cargo.toml`
+    pub manifest_dir: std::path::PathBuf,
+    /// `Cargo.lock` of the workspace the package was resolved in
+    pub lockfile: std::path::PathBuf,
+    pub feature_scope: FeatureScope,
+    /// `<docs.rs>/<name>/<version>` for a crate read from docs.rs instead of built locally
+    pub docs_rs_url: Option<String>,
+    /// standard library docs of the rustup `rust-docs` component, read in place, never built
+    pub std_docs: bool,
 }
 
-/// Extract symbol listings (modules, macros, structs, enums, functions, types) from index.html
-pub async fn extract_symbols(html: &str, crate_name: &str) -> Result<Vec<SymbolInfo>, String> {
-    use std::collections::VecDeque;
+impl DocTarget {
+    pub fn new(metadata: &cargo_metadata::Metadata, pkg: &cargo_metadata::Package) -> Self {
+        Self {
+            crate_id: format!("{}@{}", pkg.name, pkg.version),
+            version: pkg.version.to_string(),
+            spec: package_spec(pkg),
+            doc_name: doc_dir_name(pkg),
+            doc_root: target_dir(metadata).join("doc"),
+            triple: None,
+            source: describe_source(pkg),
+            manifest_dir: pkg
+                .manifest_path
+                .parent()
+                .map_or_else(Default::default, |p| p.as_std_path().to_path_buf()),
+            lockfile: metadata
+                .workspace_root
+                .join("Cargo.lock")
+                .into_std_path_buf(),
+            feature_scope: feature_scope(metadata, pkg),
+            docs_rs_url: None,
+            std_docs: false,
+        }
+    }
+
+    /// Document for `triple` instead of the host, see [`DocBuildArgs::target`]. docs.rs targets
+    /// keep the pages docs.rs built for its default target.
+    pub fn with_triple(mut self, triple: Option<&str>) -> Self {
+        if let Some(triple) = triple.filter(|_| self.docs_rs_url.is_none() && !self.std_docs) {
+            let target_dir = self.doc_root.parent().map(std::path::Path::to_path_buf);
+            self.doc_root = target_dir.unwrap_or_default().join(triple).join("doc");
+            self.triple = Some(triple.to_string());
+        }
+        self
+    }
 
-    // queue of (html_string, base_dir)
-    let mut queue: VecDeque<(String, std::path::PathBuf)> = VecDeque::new();
-    queue.push_back((html.to_string(), std::path::PathBuf::from("")));
+    /// Generated docs of this package, `<doc_root>/<doc_name>`
+    pub fn doc_dir(&self) -> std::path::PathBuf {
+        self.doc_root.join(&self.doc_name)
+    }
 
-    let mut symbols: Vec<SymbolInfo> = Vec::new();
-    let mut visited: std::collections::HashSet<String> = std::collections::HashSet::new();
+    /// Whether the package is published on crates.io (as opposed to git, path or another registry)
+    pub fn is_crates_io(&self) -> bool {
+        self.source == "crates-io"
+    }
+}
 
-    while let Some((page_html, base_dir)) = queue.pop_front() {
-        // process page synchronously
-        let (page_symbols, modules) = process_page(&page_html, &base_dir);
-        symbols.extend(page_symbols);
+fn feature_scope(
+    metadata: &cargo_metadata::Metadata,
+    pkg: &cargo_metadata::Package,
+) -> FeatureScope {
+    if metadata.workspace_members.contains(&pkg.id) {
+        return FeatureScope::Member;
+    }
+    let nodes = metadata.resolve.as_ref().map_or(&[][..], |r| &r.nodes[..]);
+    nodes
+        .iter()
+        .filter(|node| metadata.workspace_members.contains(&node.id))
+        .filter(|node| node.deps.iter().any(|dep| dep.pkg == pkg.id))
+        .find_map(|node| {
+            let member = &metadata[&node.id];
+            let dep = member
+                .dependencies
+                .iter()
+                .find(|dep| dep.name == pkg.name && dep.req.matches(&pkg.version))?;
+            Some(FeatureScope::Dependency {
+                member: package_spec(member),
+                member_doc_name: doc_dir_name(member),
+                dependency: dep.rename.clone().unwrap_or_else(|| dep.name.clone()),
+                all: pkg.features.keys().cloned().collect(),
+            })
+        })
+        .unwrap_or(FeatureScope::Unreachable)
+}
 
-        // schedule modules to visit
-        for module_path in modules {
-            if visited.contains(&module_path) {
-                continue;
+/// `cargo doc` arguments enabling the requested features of the target
+fn feature_args(target: &DocTarget, options: &DocOptions) -> Vec<String> {
+    if options.features.is_empty() && !options.all_features {
+        return Vec::new();
+    }
+    match &target.feature_scope {
+        FeatureScope::Dependency {
+            member,
+            dependency,
+            all,
+            ..
+        } => {
+            let features = if options.all_features {
+                all
+            } else {
+                &options.features
+            };
+            let features: Vec<String> = features
+                .iter()
+                .map(|feature| format!("{}/{}", dependency, feature))
+                .collect();
+            vec![
+                "--package".to_string(),
+                member.clone(),
+                "--features".to_string(),
+                features.join(","),
+            ]
+        }
+        FeatureScope::Member | FeatureScope::Unreachable => {
+            let mut args = Vec::new();
+            if options.all_features {
+                args.push("--all-features".to_string());
             }
-            // attempt to read module html
-            match read_doc_html_by_rel_path(crate_name, &module_path).await {
-                Ok(module_html) => {
-                    let parent = std::path::Path::new(&module_path)
-                        .parent()
-                        .unwrap_or(std::path::Path::new(""))
-                        .to_path_buf();
-                    queue.push_back((module_html, parent));
-                    visited.insert(module_path);
-                }
-                Err(_) => {
-                    // ignore missing module page
-                }
+            if !options.features.is_empty() {
+                args.push("--features".to_string());
+                args.push(options.features.join(","));
             }
+            args
         }
     }
-
-    Ok(symbols)
 }
 
-/// Process a single page synchronously and extract SymbolInfo entries and module links to visit
-pub fn process_page(html: &str, base_dir: &std::path::Path) -> (Vec<SymbolInfo>, Vec<String>) {
-    let document = scraper::Html::parse_document(html);
-
-    // Mapping of section id -> symbol_type string
-    let sections = vec![
-        ("modules", "module"),
-        ("macros", "macro"),
-        ("structs", "struct"),
-        ("enums", "enum"),
-        ("functions", "function"),
-        ("types", "type_alias"),
-    ];
+/// Resolve a `name@version` or bare `name` crate id against cargo metadata
+pub async fn resolve_doc_target(crate_id: &str) -> Result<DocTarget, CopilotError> {
+    if crate::sysroot::is_std_crate(crate_id) {
+        return crate::sysroot::resolve(crate_id).await;
+    }
+    let metadata = get_metadata().await?;
+    match find_package(&metadata, crate_id) {
+        Ok(pkg) => Ok(DocTarget::new(&metadata, pkg)),
+        Err(CopilotError::PackageNotFound { .. }) if crate::docsrs::enabled() => {
+            crate::docsrs::resolve(&metadata, crate_id).await
+        }
+        Err(e) => Err(e),
+    }
+}
 
-    let mut out = Vec::new();
-    let mut modules_to_visit = Vec::new();
+/// Build output directory: cargo's resolved `target_directory` (which accounts for
+/// `CARGO_TARGET_DIR` and `build.target-dir`), then `CARGO_TARGET_DIR`, then `target`
+pub fn target_dir(metadata: &cargo_metadata::Metadata) -> std::path::PathBuf {
+    if !metadata.target_directory.as_str().is_empty() {
+        return metadata.target_directory.as_std_path().to_path_buf();
+    }
+    std::env::var_os("CARGO_TARGET_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| "target".into(), std::path::PathBuf::from)
+}
 
-    for (section_id, symbol_type) in sections {
-        let selector_str = format!("h2#{} + dl.item-table", section_id);
-        let dl_selector = match scraper::Selector::parse(&selector_str) {
-            Ok(s) => s,
-            Err(_) => continue,
-        };
+/// rustdoc writes docs under the crate name of the lib target (which `[lib] name` may set to
+/// anything) with hyphens replaced by underscores; bin-only packages are documented by binary
+pub fn doc_dir_name(pkg: &cargo_metadata::Package) -> String {
+    const LIB_KINDS: &[&str] = &["lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro"];
+    let name = pkg
+        .targets
+        .iter()
+        .find(|t| t.kind.iter().any(|k| LIB_KINDS.contains(&k.as_str())))
+        .or_else(|| {
+            pkg.targets
+                .iter()
+                .find(|t| t.kind.iter().any(|k| k == "bin"))
+        })
+        .map_or(pkg.name.as_str(), |t| t.name.as_str());
+    name.replace('-', "_")
+}
 
-        for dl in document.select(&dl_selector) {
-            let pair_selector = match scraper::Selector::parse("dt, dd") {
-                Ok(s) => s,
-                Err(_) => continue,
-            };
+/// Package id spec for `--package`; cargo >= 1.77 reports ids in spec form already
+pub fn package_spec(pkg: &cargo_metadata::Package) -> String {
+    if pkg.id.repr.contains('#') {
+        pkg.id.repr.clone()
+    } else {
+        format!("{}@{}", pkg.name, pkg.version)
+    }
+}
 
-            let mut iter = dl.select(&pair_selector);
-            while let Some(item) = iter.next() {
-                if item.value().name() == "dt"
-                    && let Some(a) = item.select(&scraper::Selector::parse("a").unwrap()).next()
-                {
-                    let symbol_id = a.text().collect::<Vec<_>>().join("").trim().to_string();
-                    let href = a.value().attr("href").unwrap_or("").to_string();
-
-                    // Compute full relative path (normalize)
-                    let full_path = if base_dir.as_os_str().is_empty() {
-                        std::path::Path::new(&href).to_path_buf()
-                    } else {
-                        base_dir.join(&href)
-                    };
-                    let full_path = normalize_rel_path(&full_path);
-                    let full_path_str = full_path.to_string_lossy().replace("\\", "/");
-
-                    // next should be dd (optional)
-                    let mut desc: Option<String> = None;
-                    if let Some(next_item) = iter.next()
-                        && next_item.value().name() == "dd"
-                    {
-                        let dd_html = next_item.inner_html();
-                        let dd_md = html2md::parse_html(&dd_html);
-                        let dd_trim = dd_md.trim().to_string();
-                        if !dd_trim.is_empty() {
-                            desc = Some(dd_trim);
-                        }
-                    }
+/// Human readable package source: `crates-io`, `git <url>`, `registry <url>` or `path <dir>`
+pub fn describe_source(pkg: &cargo_metadata::Package) -> String {
+    match &pkg.source {
+        Some(source) if source.is_crates_io() => "crates-io".to_string(),
+        Some(source) => match source.repr.split_once('+') {
+            Some(("git", url)) => format!("git {}", url),
+            Some((_, url)) => format!("registry {}", url),
+            None => source.repr.clone(),
+        },
+        None => format!(
+            "path {}",
+            pkg.manifest_path
+                .parent()
+                .map_or(pkg.manifest_path.as_str(), |p| p.as_str())
+        ),
+    }
+}
 
-                    out.push(SymbolInfo {
-                        symbol_id: symbol_id.clone(),
-                        symbol_path: full_path_str.clone(),
-                        symbol_type: symbol_type.to_string(),
-                        symbol_description: desc,
-                    });
+/// Run `cargo doc --package <spec> --no-deps` to generate stable HTML docs
+pub async fn doc(target: &DocTarget) -> Result<(), CopilotError> {
+    doc_with_options(target, &DocOptions::default()).await
+}
 
-                    if symbol_type == "module" {
-                        modules_to_visit.push(full_path_str.clone());
-                    }
+/// Same as [`doc`] with explicit [`DocOptions`].
+///
+/// Builds are skipped when the docs were already generated by this process with the same
+/// options and neither `Cargo.lock` nor the package sources changed since. Docs left by an
+/// earlier run are reused for the default options when their `index.html` is newer than both.
+/// Set `CARGO_COPILOT_NO_CACHE=1` to always rebuild.
+pub async fn doc_with_options(
+    target: &DocTarget,
+    options: &DocOptions,
+) -> Result<(), CopilotError> {
+    // docs.rs pages are fetched as they are read, built with whatever options docs.rs used, and
+    // the standard library docs come prebuilt with the toolchain
+    if target.docs_rs_url.is_some() || target.std_docs {
+        return Ok(());
+    }
+    let use_cache = !env_flag("CARGO_COPILOT_NO_CACHE");
+    let is_cached = |key: &Option<String>| {
+        use_cache
+            && key
+                .as_ref()
+                .is_some_and(|key| DOC_CACHE.lock().unwrap().get(&cache_slot(target)) == Some(key))
+    };
+    let key = doc_cache_key(target, options).await;
+    if is_cached(&key) {
+        tracing::debug!(crate_id = %target.crate_id, "docs are up to date");
+        return Ok(());
+    }
+    // the output doesn't record the options it was built with, so only docs this process
+    // didn't build are trusted, and only for the defaults
+    if use_cache
+        && options.is_default()
+        && !DOC_CACHE.lock().unwrap().contains_key(&cache_slot(target))
+        && docs_newer_than_sources(target).await
+    {
+        tracing::debug!(crate_id = %target.crate_id, "docs are newer than the sources");
+        if let Some(key) = key {
+            DOC_CACHE.lock().unwrap().insert(cache_slot(target), key);
+        }
+        return Ok(());
+    }
+
+    // builds share the target dir, so they run one at a time; the docs may have been built by
+    // the call holding the permit while this one waited
+    let _permit = DOC_BUILDS
+        .acquire()
+        .await
+        .expect("doc build semaphore is never closed");
+    let key = doc_cache_key(target, options).await;
+    if is_cached(&key) {
+        return Ok(());
+    }
+
+    // every version of a package documents into the same directory and cargo considers a version
+    // fresh as long as that output exists, so drop docs left behind by another version
+    let doc_dir = target.doc_dir();
+    if let Ok(index) = tokio::fs::read_to_string(doc_dir.join("index.html")).await
+        && doc_version(&index).is_some_and(|v| v != target.version)
+    {
+        tokio::fs::remove_dir_all(&doc_dir)
+            .await
+            .map_err(|e| CopilotError::io("remove", &doc_dir, e))?;
+    }
+
+    if let Some(triple) = &target.triple {
+        check_target_installed(triple).await?;
+    }
+
+    let mut cmd = cargo_command();
+    cmd.arg("doc")
+        .arg("--package")
+        .arg(&target.spec)
+        .arg("--no-deps");
+    if let Some(triple) = &target.triple {
+        cmd.arg("--target").arg(triple);
+    }
+    let offline = offline();
+    if offline {
+        cmd.arg("--offline");
+    }
+    if options.document_private_items {
+        cmd.arg("--document-private-items");
+    }
+    let features = feature_args(target, options);
+    // the member is documented too, so its docs no longer match the options they were cached for
+    if !features.is_empty()
+        && let FeatureScope::Dependency {
+            member_doc_name, ..
+        } = &target.feature_scope
+    {
+        let member_slot = target.doc_root.join(member_doc_name);
+        DOC_CACHE
+            .lock()
+            .unwrap()
+            .remove(member_slot.to_string_lossy().as_ref());
+    }
+    cmd.args(features);
+    if let Some(flags) = rustdoc_flags(options) {
+        cmd.env("RUSTDOCFLAGS", flags);
+    }
+
+    tracing::info!(crate_id = %target.crate_id, ?options, "running cargo doc");
+    let started = std::time::Instant::now();
+    let mut child = cmd
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|source| CopilotError::Spawn {
+            command: "cargo doc",
+            source,
+        })?;
+    let mut group = ProcessGroupGuard::new(&child);
+    // drain stderr while waiting so a chatty build can't fill the pipe and stall
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stderr = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        let _ = tokio::io::AsyncReadExt::read_to_end(&mut stderr_pipe, &mut buf).await;
+        buf
+    });
+
+    let timeout = build_timeout();
+    let status = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => status.map_err(|e| CopilotError::io("wait for", "cargo doc", e))?,
+        Err(_) => {
+            DOC_CACHE.lock().unwrap().remove(&cache_slot(target));
+            let _ = child.kill().await;
+            tracing::warn!(crate_id = %target.crate_id, "cargo doc timed out");
+            return Err(CopilotError::CargoDocTimeout(timeout));
+        }
+    };
+    group.disarm();
+    let stderr = stderr.await?;
+    tracing::info!(
+        crate_id = %target.crate_id,
+        %status,
+        elapsed = ?started.elapsed(),
+        "cargo doc finished"
+    );
+
+    if !status.success() {
+        DOC_CACHE.lock().unwrap().remove(&cache_slot(target));
+        return Err(CopilotError::CargoDocFailed {
+            status,
+            offline,
+            stderr: String::from_utf8_lossy(&stderr).trim().to_string(),
+        });
+    }
+
+    match key {
+        Some(key) => DOC_CACHE.lock().unwrap().insert(cache_slot(target), key),
+        None => DOC_CACHE.lock().unwrap().remove(&cache_slot(target)),
+    };
+
+    Ok(())
+}
+
+/// Sysroot of the active toolchain from `rustc --print sysroot`, `None` when rustc can't be run
+pub async fn sysroot() -> Option<std::path::PathBuf> {
+    let output = tokio::process::Command::new("rustc")
+        .args(["--print", "sysroot"])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !sysroot.is_empty()).then(|| sysroot.into())
+}
+
+/// Fail fast with the installed targets when the standard library of `triple` is missing from
+/// the sysroot, which cargo would only report after compiling the dependencies. Skipped when
+/// the sysroot can't be located; cargo has the last word then.
+async fn check_target_installed(triple: &str) -> Result<(), CopilotError> {
+    let Some(sysroot) = sysroot().await else {
+        return Ok(());
+    };
+    let rustlib = sysroot.join("lib").join("rustlib");
+    if rustlib.join(triple).join("lib").is_dir() {
+        return Ok(());
+    }
+
+    let mut installed = Vec::new();
+    if let Ok(mut entries) = tokio::fs::read_dir(&rustlib).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.path().join("lib").is_dir() {
+                installed.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+    }
+    installed.sort();
+    Err(CopilotError::TargetNotInstalled {
+        triple: triple.to_string(),
+        installed,
+    })
+}
+
+/// `RUSTDOCFLAGS` for a doc build: `CARGO_COPILOT_RUSTDOCFLAGS` when set, else the inherited
+/// `RUSTDOCFLAGS`, plus `--cfg docsrs` for [`DocOptions::docsrs`]. `None` leaves the variable
+/// as inherited.
+fn rustdoc_flags(options: &DocOptions) -> Option<String> {
+    let configured = std::env::var("CARGO_COPILOT_RUSTDOCFLAGS")
+        .or_else(|_| std::env::var("RUSTDOCFLAGS"))
+        .ok()
+        .filter(|flags| !flags.trim().is_empty());
+    match (configured, options.docsrs) {
+        (Some(flags), true) if flags.contains("--cfg docsrs") => Some(flags),
+        (Some(flags), true) => Some(format!("{} --cfg docsrs", flags.trim())),
+        (None, true) => Some("--cfg docsrs".to_string()),
+        (flags, false) => flags,
+    }
+}
+
+const DEFAULT_BUILD_TIMEOUT_SECS: u64 = 120;
+
+/// Upper bound for a single `cargo doc` run or snippet check, `CARGO_COPILOT_DOC_TIMEOUT_SECS`
+/// or 120s
+pub fn build_timeout() -> std::time::Duration {
+    let secs = std::env::var("CARGO_COPILOT_DOC_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_BUILD_TIMEOUT_SECS);
+    std::time::Duration::from_secs(secs)
+}
+
+/// Whether a boolean env var is set to anything but empty or `0`
+pub fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Package version shown in the sidebar of a rustdoc page
+pub fn doc_version(html: &str) -> Option<&str> {
+    const MARKER: &str = "<span class=\"version\">";
+    let start = html.find(MARKER)? + MARKER.len();
+    let end = html[start..].find('<')?;
+    Some(html[start..start + end].trim())
+}
+
+/// Doc directory -> key of the last successful `cargo doc` run writing to it. Other versions of
+/// the package and builds with different options overwrite the same `target/doc` output.
+static DOC_CACHE: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<String, String>>> =
+    std::sync::LazyLock::new(Default::default);
+
+fn cache_slot(target: &DocTarget) -> String {
+    target.doc_dir().to_string_lossy().into_owned()
+}
+
+/// Limits concurrent `cargo doc` runs, one by default. `CARGO_COPILOT_DOC_JOBS` allows more for
+/// setups where the builds don't contend on the same target dir.
+static DOC_BUILDS: std::sync::LazyLock<tokio::sync::Semaphore> = std::sync::LazyLock::new(|| {
+    let permits = std::env::var("CARGO_COPILOT_DOC_JOBS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(1);
+    tokio::sync::Semaphore::new(permits)
+});
+
+/// Cache key for a doc build: the package spec, the options, a hash of `Cargo.lock` and the newest mtime under
+/// the package directory. `None` when the generated docs are missing or the inputs can't be read.
+async fn doc_cache_key(target: &DocTarget, options: &DocOptions) -> Option<String> {
+    let index = target.doc_dir().join("index.html");
+    if !tokio::fs::try_exists(&index).await.unwrap_or(false) {
+        return None;
+    }
+
+    let lock = tokio::fs::read(&target.lockfile).await.ok()?;
+    let manifest_dir = target.manifest_dir.clone();
+    let mtime = tokio::task::spawn_blocking(move || newest_mtime(&manifest_dir))
+        .await
+        .ok()??;
+
+    Some(format!(
+        "spec={};triple={:?};docsrs={};private={};features={:?};all_features={};lock={:016x};mtime={}",
+        target.spec,
+        target.triple,
+        options.docsrs,
+        options.document_private_items,
+        options.features,
+        options.all_features,
+        fnv1a64(&lock),
+        mtime.as_nanos()
+    ))
+}
+
+/// Whether `index.html` documents the target's version and was written after `Cargo.lock` and
+/// every file of the package changed
+async fn docs_newer_than_sources(target: &DocTarget) -> bool {
+    let index = target.doc_dir().join("index.html");
+    let modified = |path: &std::path::Path| {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+    };
+    let Some(generated) = modified(&index) else {
+        return false;
+    };
+    let Ok(html) = tokio::fs::read_to_string(&index).await else {
+        return false;
+    };
+    if doc_version(&html).is_some_and(|v| v != target.version) {
+        return false;
+    }
+
+    let lockfile = target.lockfile.clone();
+    let manifest_dir = target.manifest_dir.clone();
+    tokio::task::spawn_blocking(move || {
+        modified(&lockfile).is_some_and(|lock| lock < generated)
+            && newest_mtime(&manifest_dir).is_some_and(|sources| sources < generated)
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Newest modification time of any file under `dir`, skipping `target` and hidden directories
+fn newest_mtime(dir: &std::path::Path) -> Option<std::time::Duration> {
+    let mut newest = std::time::Duration::ZERO;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).ok()?.flatten() {
+            let file_type = entry.file_type().ok()?;
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name != "target" && !name.starts_with('.') {
+                    pending.push(entry.path());
+                }
+                continue;
+            }
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+            newest = newest.max(since_epoch);
+        }
+    }
+    Some(newest)
+}
+
+/// A crate directory with an `index.html` found under `<target dir>/doc`
+#[derive(Debug, Clone)]
+pub struct DocDir {
+    /// directory name, the crate name rustdoc documented
+    pub doc_name: String,
+    /// package version shown on the generated `index.html`
+    pub version: Option<String>,
+    /// package of the metadata the docs were generated for, `None` for leftovers of a package
+    /// that is no longer in the dependency graph (or got upgraded since)
+    pub target: Option<DocTarget>,
+}
+
+/// Crate docs already generated in the target dir, sorted by directory name. Empty when
+/// nothing was documented yet.
+pub async fn scan_doc_dirs(
+    metadata: &cargo_metadata::Metadata,
+) -> Result<Vec<DocDir>, CopilotError> {
+    let doc_root = target_dir(metadata).join("doc");
+    let mut entries = match tokio::fs::read_dir(&doc_root).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(CopilotError::io("read", doc_root, e)),
+    };
+
+    let mut dirs = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| CopilotError::io("read", &doc_root, e))?
+    {
+        // `src`, `static.files` and the like have no index page
+        let Ok(html) = tokio::fs::read_to_string(entry.path().join("index.html")).await else {
+            continue;
+        };
+        let doc_name = entry.file_name().to_string_lossy().into_owned();
+        let version = doc_version(&html).map(str::to_string);
+        let candidates: Vec<&cargo_metadata::Package> = metadata
+            .packages
+            .iter()
+            .filter(|pkg| doc_dir_name(pkg) == doc_name)
+            .collect();
+        let pkg = match version.as_deref() {
+            Some(version) => candidates
+                .iter()
+                .find(|pkg| pkg.version.to_string() == version),
+            // rustdoc leaves the version out when cargo didn't pass one
+            None if candidates.len() == 1 => candidates.first(),
+            None => None,
+        };
+        dirs.push(DocDir {
+            target: pkg.map(|pkg| DocTarget::new(metadata, pkg)),
+            doc_name,
+            version,
+        });
+    }
+    dirs.sort_by(|a, b| a.doc_name.cmp(&b.doc_name));
+    Ok(dirs)
+}
+
+/// Read `<target dir>/doc/<crate>/index.html`
+pub async fn read_doc_index_html(target: &DocTarget) -> Result<String, CopilotError> {
+    read_doc_file(target, target.doc_dir().join("index.html")).await
+}
+
+/// Read a generated doc file, downloading it first for a docs.rs target
+async fn read_doc_file(
+    target: &DocTarget,
+    path: std::path::PathBuf,
+) -> Result<String, CopilotError> {
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => match target.docs_rs_url {
+            Some(_) => crate::docsrs::fetch_page(target, &path).await,
+            None => {
+                tracing::debug!(path = %path.display(), "doc file missing");
+                Err(CopilotError::DocFileMissing(path))
+            }
+        },
+        Err(e) => {
+            tracing::warn!(path = %path.display(), "failed to read doc file: {}", e);
+            Err(CopilotError::io("read", path, e))
+        }
+    }
+}
+
+/// Read a file relative to the doc root itself, e.g. a `src/<crate>/lib.rs.html` source page
+pub async fn read_doc_root_html(
+    target: &DocTarget,
+    rel_path: &str,
+) -> Result<String, CopilotError> {
+    read_doc_file(target, target.doc_root.join(rel_path)).await
+}
+
+/// Read an arbitrary doc HTML file relative to the crate doc dir, e.g., "de/index.html" or "struct.Error.html"
+pub async fn read_doc_html_by_rel_path(
+    target: &DocTarget,
+    rel_path: &str,
+) -> Result<String, CopilotError> {
+    read_doc_file(target, target.doc_dir().join(rel_path)).await
+}
+
+/// Turn a `symbol_path` argument into a doc file path relative to the crate doc dir. A
+/// `#fragment` (as left by [`rewrite_doc_links`]) is dropped.
+pub fn symbol_rel_path(symbol_path: &str) -> String {
+    let path = symbol_path.split('#').next().unwrap_or_default();
+    let mut rel = path.trim().trim_start_matches('/').to_string();
+    if !rel.ends_with(".html") {
+        rel.push_str(".html");
+    }
+    rel
+}
+
+static HREF: std::sync::LazyLock<regex::Regex> =
+    std::sync::LazyLock::new(|| regex::Regex::new(r#"href="([^"]*)""#).unwrap());
+
+/// Rewrite links between pages of the crate docs into the `symbol_path` form the doc tools
+/// accept (`../struct.Foo.html#method.new` -> `struct.Foo#method.new`), resolved against
+/// `base_dir`, the directory of the page the HTML comes from. External links, same-page anchors
+/// and links leaving the crate docs (other crates, source pages) are kept as is.
+pub fn rewrite_doc_links(html: &str, base_dir: &std::path::Path) -> String {
+    HREF.replace_all(html, |caps: &regex::Captures| {
+        let href = &caps[1];
+        let (path, fragment) = match href.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (href, None),
+        };
+        let internal = !path.is_empty() && !path.contains(':') && path.ends_with(".html");
+        let joined = base_dir.join(path);
+        if !internal || escapes_doc_dir(&joined) {
+            return caps[0].to_string();
+        }
+
+        let resolved = normalize_rel_path(&joined)
+            .to_string_lossy()
+            .replace("\\", "/");
+        let mut symbol_path = resolved.trim_end_matches(".html").to_string();
+        if let Some(fragment) = fragment {
+            symbol_path.push('#');
+            symbol_path.push_str(fragment);
+        }
+        format!("href=\"{}\"", symbol_path)
+    })
+    .into_owned()
+}
+
+/// Whether a relative path climbs above the directory it is relative to
+fn escapes_doc_dir(path: &std::path::Path) -> bool {
+    let mut depth = 0usize;
+    for comp in path.components() {
+        match comp {
+            std::path::Component::ParentDir if depth == 0 => return true,
+            std::path::Component::ParentDir => depth -= 1,
+            std::path::Component::Normal(_) => depth += 1,
+            std::path::Component::CurDir => {}
+            _ => return true,
+        }
+    }
+    false
+}
+
+/// Symbol name encoded in a doc file path, e.g. `de/struct.Deserializer.html` -> `Deserializer`
+pub fn symbol_name(rel_path: &str) -> Option<String> {
+    let stem = std::path::Path::new(rel_path).file_stem()?.to_str()?;
+    stem.split_once('.').map(|(_, name)| name.to_string())
+}
+
+/// Inner HTML of the top-level docs of a crate or module page: the `div.docblock`s directly under
+/// `section#main-content` (or in its `details.top-doc` toggle) before the first item section,
+/// joined. Item summaries in the tables below and docs of re-exported items are left out. Falls
+/// back to the first `div.docblock` of a page without `section#main-content`.
+pub fn extract_docblock(html: &str) -> Option<String> {
+    let document = if html.len() > LARGE_PAGE_BYTES {
+        // the top-level docs always come before the first item section
+        let end = find_section_header(html, 0).unwrap_or(html.len());
+        scraper::Html::parse_fragment(&html[..end])
+    } else {
+        scraper::Html::parse_document(html)
+    };
+    let main_selector = scraper::Selector::parse("section#main-content").ok()?;
+    let docblock_selector = scraper::Selector::parse("div.docblock").ok()?;
+
+    let is_docblock = |el: &scraper::ElementRef| {
+        el.value().name() == "div" && el.value().classes().any(|c| c == "docblock")
+    };
+    let Some(main) = document.select(&main_selector).next() else {
+        return document
+            .select(&docblock_selector)
+            .next()
+            .map(|el| el.inner_html());
+    };
+    let mut blocks = Vec::new();
+    for child in main.children().filter_map(scraper::ElementRef::wrap) {
+        let value = child.value();
+        if value.name() == "h2" && value.classes().any(|c| c == "section-header") {
+            break;
+        }
+        if is_docblock(&child) {
+            blocks.push(child.inner_html());
+        } else if value.name() == "details" && value.classes().any(|c| c == "top-doc") {
+            blocks.extend(
+                child
+                    .children()
+                    .filter_map(scraper::ElementRef::wrap)
+                    .filter(is_docblock)
+                    .map(|el| el.inner_html()),
+            );
+        }
+    }
+
+    (!blocks.is_empty()).then(|| blocks.join("\n"))
+}
+
+/// Extract the plain-text item declaration (`pre.item-decl`) of a symbol page
+pub fn extract_signature(html: &str) -> Option<String> {
+    let document = scraper::Html::parse_document(html);
+    let selector = scraper::Selector::parse("pre.item-decl").ok()?;
+    let decl = document.select(&selector).next()?;
+    let mut text = String::new();
+    for node in decl.descendants() {
+        if let Some(t) = node.value().as_text() {
+            text.push_str(t);
+            // attributes are blocks rendered right before the item, without a line break
+            let in_attribute = node
+                .parent()
+                .and_then(scraper::ElementRef::wrap)
+                .is_some_and(|el| el.value().classes().any(|c| c == "code-attribute"));
+            if in_attribute && !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+    }
+    Some(text.trim().to_string())
+}
+
+/// Href of the item's own `[src]` link, e.g. `../src/serde/de/mod.rs.html#536-548`
+pub fn extract_source_link(html: &str) -> Option<String> {
+    let document = scraper::Html::parse_document(html);
+    // older rustdoc versions render the link as `a.srclink`
+    let selector = scraper::Selector::parse(".main-heading a.src, a.src, a.srclink").ok()?;
+    let link = document.select(&selector).next()?;
+    link.value().attr("href").map(str::to_string)
+}
+
+/// Plain Rust source of a rustdoc `src/.../*.rs.html` page, without the line numbers
+pub fn extract_source_code(html: &str) -> Option<String> {
+    let document = scraper::Html::parse_document(html);
+    let selector = scraper::Selector::parse("pre.rust code, pre.rust").ok()?;
+    let code = document.select(&selector).next()?;
+
+    let mut out = String::new();
+    for node in code.descendants() {
+        let Some(text) = node.value().as_text() else {
+            continue;
+        };
+        // line number anchors are marked `data-nosnippet`
+        let in_line_number = node
+            .ancestors()
+            .take_while(|a| a.id() != code.id())
+            .any(|a| {
+                a.value().as_element().is_some_and(|e| {
+                    e.attr("data-nosnippet").is_some()
+                        || e.has_class("src-line-numbers", scraper::CaseSensitivity::CaseSensitive)
+                })
+            });
+        if !in_line_number {
+            out.push_str(text);
+        }
+    }
+    Some(out)
+}
+
+/// Number of module pages [`extract_symbols`] reads and parses at once
+const DEFAULT_EXTRACT_CONCURRENCY: usize = 8;
+
+/// How many doc pages to read and parse at once: `CARGO_COPILOT_CONCURRENCY`, or
+/// [`DEFAULT_EXTRACT_CONCURRENCY`]
+pub fn extract_concurrency() -> usize {
+    std::env::var("CARGO_COPILOT_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_EXTRACT_CONCURRENCY)
+}
+
+/// Extract symbol listings (modules, macros, structs, enums, functions, types, traits, constants,
+/// statics, ...) from index.html and every module page reachable from it, sorted by symbol path.
+///
+/// Module pages are processed concurrently, see [`extract_concurrency`].
+pub async fn extract_symbols(
+    html: &str,
+    target: &DocTarget,
+) -> Result<Vec<SymbolInfo>, CopilotError> {
+    extract_symbols_in(html, target, &SymbolScope::default()).await
+}
+
+/// Which part of the docs [`extract_symbols_in`] reads
+#[derive(Debug, Default, Clone)]
+pub struct SymbolScope {
+    /// module levels to descend below index.html, 0 reads the index page alone. Modules past the
+    /// limit are still listed by their parent's page
+    pub max_depth: Option<usize>,
+    /// rustdoc section ids to extract (see [`section_ids`]), all of them when empty. The
+    /// `modules` tables are still scanned to find module pages
+    pub sections: Vec<String>,
+}
+
+/// Like [`extract_symbols`], limited to a [`SymbolScope`]
+pub async fn extract_symbols_in(
+    html: &str,
+    target: &DocTarget,
+    scope: &SymbolScope,
+) -> Result<Vec<SymbolInfo>, CopilotError> {
+    let limit = extract_concurrency();
+    let within_depth = |depth: usize| scope.max_depth.is_none_or(|max| depth <= max);
+
+    let index_html = html.to_string();
+    let (mut symbols, modules, mut aliases) = {
+        let crate_name = target.doc_name.clone();
+        let sections = scope.sections.clone();
+        parse_blocking(move || {
+            let base_dir = std::path::Path::new("");
+            let (symbols, modules) = process_page(&index_html, base_dir, &crate_name, &sections);
+            let aliases = reexport_aliases(&index_html, base_dir, &crate_name);
+            (symbols, modules, aliases)
+        })
+        .await?
+    };
+
+    let mut visited: std::collections::HashSet<String> = modules.iter().cloned().collect();
+    // `(module page, depth)`, the modules listed on index.html being at depth 1
+    let mut pending: std::collections::VecDeque<(String, usize)> = if within_depth(1) {
+        modules.into_iter().map(|module| (module, 1)).collect()
+    } else {
+        Default::default()
+    };
+    let mut tasks = tokio::task::JoinSet::new();
+
+    loop {
+        while tasks.len() < limit
+            && let Some((module_path, depth)) = pending.pop_front()
+        {
+            let target = target.clone();
+            let sections = scope.sections.clone();
+            tasks.spawn(async move {
+                // ignore missing module page
+                let module_html = match read_doc_html_by_rel_path(&target, &module_path).await {
+                    Ok(html) => html,
+                    Err(e) => {
+                        tracing::warn!(crate_id = %target.crate_id, "skipping module page: {}", e);
+                        return Ok(None);
+                    }
+                };
+                let parent = std::path::Path::new(&module_path)
+                    .parent()
+                    .unwrap_or(std::path::Path::new(""))
+                    .to_path_buf();
+                parse_blocking(move || {
+                    let (symbols, modules) =
+                        process_page(&module_html, &parent, &target.doc_name, &sections);
+                    let aliases = reexport_aliases(&module_html, &parent, &target.doc_name);
+                    (symbols, modules, aliases, depth)
+                })
+                .await
+                .map(Some)
+            });
+        }
+
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let Some((page_symbols, modules, page_aliases, depth)) = joined?? else {
+            continue;
+        };
+        symbols.extend(page_symbols);
+        aliases.extend(page_aliases);
+        if !within_depth(depth + 1) {
+            continue;
+        }
+        for module_path in modules {
+            if visited.insert(module_path.clone()) {
+                pending.push_back((module_path, depth + 1));
+            }
+        }
+    }
+
+    // pages complete in arbitrary order
+    symbols.sort_by(|a, b| a.symbol_path.cmp(&b.symbol_path));
+    let mut symbols = dedup_symbols(symbols);
+    for (symbol_path, public_path) in aliases {
+        if let Ok(i) = symbols.binary_search_by(|s| s.symbol_path.cmp(&symbol_path)) {
+            symbols[i].reexported_as.push(public_path);
+        }
+    }
+    for symbol in &mut symbols {
+        symbol.reexported_as.sort();
+        symbol.reexported_as.dedup();
+    }
+    Ok(symbols)
+}
+
+/// `(symbol_path, public path)` of the items a page re-exports with a plain `pub use`, which
+/// rustdoc lists under "Re-exports" instead of the item tables. Globs are left out.
+fn reexport_aliases(
+    html: &str,
+    base_dir: &std::path::Path,
+    crate_name: &str,
+) -> Vec<(String, String)> {
+    if !html.contains("id=\"reexports\"") {
+        return Vec::new();
+    }
+    extract_reexports(html, base_dir, &module_rust_path(crate_name, base_dir))
+        .into_iter()
+        .filter(|r| !r.public_path.ends_with("::*"))
+        .filter_map(|r| Some((r.symbol_path?, r.public_path)))
+        .collect()
+}
+
+/// Rust path of the module documented in `base_dir`, e.g. `serde::de` for `de`
+fn module_rust_path(crate_name: &str, base_dir: &std::path::Path) -> String {
+    std::iter::once(crate_name)
+        .chain(base_dir.iter().filter_map(|c| c.to_str()))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Merge entries of the same page (sorted by `symbol_path`) listed by several modules, keeping
+/// the path of the defining module and collecting the others in `reexported_as`. `visited` in
+/// [`extract_symbols`] only keeps module pages from being walked twice.
+fn dedup_symbols(mut symbols: Vec<SymbolInfo>) -> Vec<SymbolInfo> {
+    symbols.dedup_by(|dup, kept| {
+        if dup.symbol_path != kept.symbol_path {
+            return false;
+        }
+        kept.reexported_as.append(&mut dup.reexported_as);
+        true
+    });
+    symbols
+}
+
+/// First paragraph of a description, cut after its first sentence; a `. ` after `e.g`/`i.e`
+/// doesn't end one. The markdown converter wraps long lines, so the paragraph's lines are
+/// joined first.
+fn summary(description: &str) -> String {
+    let line = description
+        .lines()
+        .map(str::trim)
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = line.as_str();
+    let mut from = 0;
+    while let Some(i) = line[from..].find(". ").map(|i| from + i) {
+        if !(line[..i].ends_with("e.g") || line[..i].ends_with("i.e")) {
+            return line[..=i].to_string();
+        }
+        from = i + 2;
+    }
+    line.to_string()
+}
+
+/// Rust path of the symbol documented at `rel_path`: each directory is a module, `index.html`
+/// is the page of the innermost one. Links into another crate's docs start with `../<crate>/`.
+fn qualified_name(crate_name: &str, rel_path: &str, symbol_id: &str) -> String {
+    let mut parts: Vec<&str> = rel_path.split('/').collect();
+    let file = parts.pop().unwrap_or_default();
+    let (root, modules) = match parts.as_slice() {
+        ["..", other, modules @ ..] => (*other, modules),
+        modules => (crate_name, modules),
+    };
+    let mut path = vec![root];
+    path.extend(modules);
+    if file != "index.html" {
+        path.push(symbol_id);
+    }
+    path.join("::")
+}
+
+/// Item table section id on a module page -> `symbol_type` of its entries
+const ITEM_SECTIONS: &[(&str, &str)] = &[
+    ("modules", "module"),
+    ("macros", "macro"),
+    ("structs", "struct"),
+    ("enums", "enum"),
+    ("functions", "function"),
+    ("types", "type_alias"),
+    ("traits", "trait"),
+    ("constants", "constant"),
+    ("statics", "static"),
+    ("unions", "union"),
+    ("trait-aliases", "trait_alias"),
+    // proc-macro crates list these apart from `macros`
+    ("attributes", "attribute_macro"),
+    ("derives", "derive_macro"),
+];
+
+/// Every `symbol_type` [`extract_symbols`] can produce
+pub fn symbol_types() -> impl Iterator<Item = &'static str> {
+    ITEM_SECTIONS.iter().map(|(_, symbol_type)| *symbol_type)
+}
+
+/// Every rustdoc section id [`process_page`] scans, e.g. `traits`
+pub fn section_ids() -> impl Iterator<Item = &'static str> {
+    ITEM_SECTIONS.iter().map(|(section_id, _)| *section_id)
+}
+
+/// Process a single page synchronously and extract SymbolInfo entries and module links to visit.
+/// Only the `sections` ids are extracted when not empty; `modules` is scanned either way for the
+/// links.
+pub fn process_page(
+    html: &str,
+    base_dir: &std::path::Path,
+    crate_name: &str,
+    sections: &[String],
+) -> (Vec<SymbolInfo>, Vec<String>) {
+    let wanted = |section_id: &str| sections.is_empty() || sections.iter().any(|s| s == section_id);
+    // building a DOM for a multi-megabyte page is slow, so large pages, and pages only some
+    // sections are read from, only parse the slice of each section that is actually scanned below
+    let full_document = (sections.is_empty() && html.len() <= LARGE_PAGE_BYTES)
+        .then(|| scraper::Html::parse_document(html));
+
+    let deprecated_selector = scraper::Selector::parse(".stab.deprecated").unwrap();
+    let unstable_selector = scraper::Selector::parse(".stab.unstable").unwrap();
+    let mut out = Vec::new();
+    let mut modules_to_visit = Vec::new();
+
+    for &(section_id, symbol_type) in ITEM_SECTIONS {
+        let listed = wanted(section_id);
+        if !listed && symbol_type != "module" {
+            continue;
+        }
+        let fragment;
+        let document = match &full_document {
+            Some(document) => document,
+            None => match section_slice(html, section_id) {
+                Some(slice) => {
+                    fragment = scraper::Html::parse_fragment(slice);
+                    &fragment
                 }
+                None => continue,
+            },
+        };
+
+        let entries = item_table_entries(document, section_id);
+        if entries.is_empty() && section_slice(html, section_id).is_some() {
+            // the header is there but the table markup didn't match, likely a rustdoc change
+            tracing::warn!(
+                crate_name,
+                section_id,
+                base_dir = %base_dir.display(),
+                "no item table entries found under the section header"
+            );
+        }
+        for (a, desc_el) in entries {
+            let symbol_id = a.text().collect::<Vec<_>>().join("").trim().to_string();
+            let href = a.value().attr("href").unwrap_or("").to_string();
+
+            // Compute full relative path (normalize)
+            let full_path = if base_dir.as_os_str().is_empty() {
+                std::path::Path::new(&href).to_path_buf()
+            } else {
+                base_dir.join(&href)
+            };
+            let full_path = normalize_rel_path(&full_path);
+            let full_path_str = full_path.to_string_lossy().replace("\\", "/");
+            if !listed {
+                modules_to_visit.push(full_path_str);
+                continue;
+            }
+
+            let desc = desc_el
+                .map(|el| {
+                    let html = rewrite_doc_links(&el.inner_html(), base_dir);
+                    crate::markdown::to_markdown(&html).trim().to_string()
+                })
+                .filter(|d| !d.is_empty());
+
+            // badges are rendered next to the link, inside the same `dt` / `div.item-name`
+            let badge = |selector: &scraper::Selector| {
+                a.parent()
+                    .and_then(scraper::ElementRef::wrap)
+                    .is_some_and(|name| name.select(selector).next().is_some())
+            };
+
+            let fully_qualified_name = qualified_name(crate_name, &full_path_str, &symbol_id);
+            // the rust path of the listing, which differs for items re-exported into this module
+            let listed_as = format!("{}::{}", module_rust_path(crate_name, base_dir), symbol_id);
+            out.push(SymbolInfo {
+                reexported_as: (listed_as != fully_qualified_name)
+                    .then_some(listed_as)
+                    .into_iter()
+                    .collect(),
+                deprecated: badge(&deprecated_selector),
+                unstable: badge(&unstable_selector),
+                fully_qualified_name,
+                symbol_id: symbol_id.clone(),
+                symbol_path: full_path_str.clone(),
+                symbol_type: symbol_type.to_string(),
+                summary: desc.as_deref().map(summary),
+                symbol_description: desc,
+            });
+
+            if symbol_type == "module" {
+                modules_to_visit.push(full_path_str.clone());
             }
         }
     }
@@ -304,6 +2130,138 @@ pub fn process_page(html: &str, base_dir: &std::path::Path) -> (Vec<SymbolInfo>,
     (out, modules_to_visit)
 }
 
+/// Pairs of (symbol link, optional short description) from the item table following the
+/// `h2#<section_id>` header. Handles both rustdoc markups:
+/// `<dl class="item-table"><dt><a>..</a></dt><dd>..</dd></dl>` and
+/// `<ul class="item-table"><li><div class="item-name"><a>..</a></div><div class="desc">..</div></li></ul>`
+fn item_table_entries<'a>(
+    document: &'a scraper::Html,
+    section_id: &str,
+) -> Vec<(scraper::ElementRef<'a>, Option<scraper::ElementRef<'a>>)> {
+    let link_selector = scraper::Selector::parse("a").unwrap();
+    let mut out = Vec::new();
+
+    if let Ok(dl_selector) = scraper::Selector::parse(&format!("h2#{} + dl.item-table", section_id))
+    {
+        for dl in document.select(&dl_selector) {
+            // a `dd` belongs to the `dt` right before it; undocumented items have none
+            for child in dl.children().filter_map(scraper::ElementRef::wrap) {
+                match child.value().name() {
+                    "dt" => {
+                        if let Some(a) = child.select(&link_selector).next() {
+                            out.push((a, None));
+                        }
+                    }
+                    "dd" => {
+                        if let Some(last) = out.last_mut()
+                            && last.1.is_none()
+                        {
+                            last.1 = Some(child);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Ok(ul_selector) =
+        scraper::Selector::parse(&format!("h2#{} + ul.item-table > li", section_id))
+    {
+        let name_selector = scraper::Selector::parse("div.item-name a").unwrap();
+        let desc_selector = scraper::Selector::parse("div.desc").unwrap();
+        for li in document.select(&ul_selector) {
+            if let Some(a) = li.select(&name_selector).next() {
+                out.push((a, li.select(&desc_selector).next()));
+            }
+        }
+    }
+
+    out
+}
+
+/// Extract the "Re-exports" section of a crate or module page.
+///
+/// `module_path` is the Rust path of the page (e.g. `serde::de`) and `base_dir` its directory
+/// relative to the crate docs, used to resolve links to crate-relative `symbol_path`s.
+pub fn extract_reexports(
+    html: &str,
+    base_dir: &std::path::Path,
+    module_path: &str,
+) -> Vec<ReexportInfo> {
+    let document = scraper::Html::parse_document(html);
+    let code_selector = scraper::Selector::parse("h2#reexports + .item-table code").unwrap();
+    let link_selector = scraper::Selector::parse("a[title]").unwrap();
+
+    let mut out = Vec::new();
+    for code in document.select(&code_selector) {
+        let declaration = element_text(code);
+        let glob = declaration.trim_end_matches(';').ends_with("::*");
+
+        // the last link points at the re-exported item, its title is `<kind> <canonical path>`
+        let link = code.select(&link_selector).last();
+        let (symbol_type, canonical_path) = link
+            .and_then(|a| a.value().attr("title"))
+            .and_then(|t| t.split_once(' '))
+            .map(|(kind, path)| (Some(kind.to_string()), Some(path.to_string())))
+            .unwrap_or((None, None));
+        let symbol_path = link
+            .and_then(|a| a.value().attr("href"))
+            .filter(|href| !href.starts_with("http://") && !href.starts_with("https://"))
+            .map(|href| {
+                normalize_rel_path(&base_dir.join(href))
+                    .to_string_lossy()
+                    .replace("\\", "/")
+            });
+
+        // `pub use a::B as C;` is public as `C`, otherwise the last path segment is the name
+        let name = if glob {
+            "*".to_string()
+        } else {
+            let decl = declaration.trim_end_matches(';');
+            let path = decl.split_once("use ").map_or(decl, |(_, path)| path);
+            match path.rsplit_once(" as ") {
+                Some((_, alias)) => alias.trim().to_string(),
+                None => path.rsplit("::").next().unwrap_or(path).trim().to_string(),
+            }
+        };
+
+        out.push(ReexportInfo {
+            public_path: format!("{}::{}", module_path, name),
+            canonical_path,
+            symbol_type,
+            symbol_path,
+            declaration,
+        });
+    }
+    out
+}
+
+/// Pages above this size are parsed section by section instead of as a whole document
+const LARGE_PAGE_BYTES: usize = 1 << 20;
+
+/// Byte offset of the first rustdoc section header (`<h2 id=".." class="section-header">`) at or
+/// after `from`; headings inside docblocks don't carry the `section-header` class
+fn find_section_header(html: &str, from: usize) -> Option<usize> {
+    let mut pos = from;
+    while let Some(i) = html[pos..].find("<h2 id=") {
+        let start = pos + i;
+        let tag_end = html[start..].find('>').map_or(html.len(), |j| start + j);
+        if html[start..tag_end].contains("section-header") {
+            return Some(start);
+        }
+        pos = tag_end;
+    }
+    None
+}
+
+/// Slice of the page from the header of `section_id` up to the next section header
+fn section_slice<'a>(html: &'a str, section_id: &str) -> Option<&'a str> {
+    let start = html.find(&format!("<h2 id=\"{}\"", section_id))?;
+    let end = find_section_header(html, start + 1).unwrap_or(html.len());
+    Some(&html[start..end])
+}
+
 /// Normalize a relative path, removing `./` and resolving `..` segments
 pub fn normalize_rel_path(p: &std::path::Path) -> std::path::PathBuf {
     let mut out = std::path::PathBuf::new();
@@ -320,3 +2278,881 @@ pub fn normalize_rel_path(p: &std::path::Path) -> std::path::PathBuf {
     }
     out
 }
+
+/// Collect attribute annotations for the page's item and each of its methods.
+///
+/// rustdoc only renders a subset of attributes (`must_use`, `repr`, `non_exhaustive`, ...) as
+/// `div.code-attribute`; deprecation is rendered as a `.stab.deprecated` note instead, so it is
+/// reported as a `deprecated` attribute. Items without any attribute are omitted.
+pub fn extract_item_attributes(html: &str, item_name: &str) -> Vec<ItemAttributes> {
+    let document = scraper::Html::parse_document(html);
+    let attribute_selector = scraper::Selector::parse(".code-attribute").unwrap();
+    let decl_selector = scraper::Selector::parse("pre.item-decl").unwrap();
+    let top_deprecated_selector =
+        scraper::Selector::parse("section#main-content > span.item-info .stab.deprecated").unwrap();
+    let method_selector = scraper::Selector::parse("section.method[id]").unwrap();
+
+    let mut out = Vec::new();
+
+    let mut top = Vec::new();
+    if let Some(decl) = document.select(&decl_selector).next() {
+        top.extend(decl.select(&attribute_selector).map(attribute_text));
+    }
+    if document.select(&top_deprecated_selector).next().is_some() {
+        top.push("deprecated".to_string());
+    }
+    if !top.is_empty() {
+        out.push(ItemAttributes {
+            item: item_name.to_string(),
+            attributes: top,
+        });
+    }
+
+    for method in document.select(&method_selector) {
+        let mut attributes: Vec<String> = method
+            .select(&attribute_selector)
+            .map(attribute_text)
+            .collect();
+        // the deprecation note is a sibling of the method section inside the same `<summary>`
+        let deprecated = method
+            .parent()
+            .and_then(scraper::ElementRef::wrap)
+            .filter(|parent| parent.value().name() == "summary")
+            .is_some_and(|parent| is_deprecated(&parent));
+        if deprecated {
+            attributes.push("deprecated".to_string());
+        }
+        if !attributes.is_empty() {
+            out.push(ItemAttributes {
+                item: method.value().attr("id").unwrap_or_default().to_string(),
+                attributes,
+            });
+        }
+    }
+
+    out
+}
+
+/// `#[repr(u8)]` -> `repr(u8)`
+fn attribute_text(el: scraper::ElementRef) -> String {
+    let text = el.text().collect::<String>();
+    let text = text.trim();
+    text.strip_prefix("#[")
+        .and_then(|t| t.strip_suffix(']'))
+        .unwrap_or(text)
+        .trim()
+        .to_string()
+}
+
+/// Whether an element contains a rustdoc deprecation note
+fn is_deprecated(el: &scraper::ElementRef) -> bool {
+    el.select(&scraper::Selector::parse("span.item-info .stab.deprecated").unwrap())
+        .next()
+        .is_some()
+}
+
+/// 64-bit FNV-1a hash; unlike `DefaultHasher` the output is stable across Rust releases
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Collect every cfg-variant of the page's item and its methods.
+///
+/// rustdoc disambiguates methods defined more than once (e.g. a unix and a windows form) with
+/// `-1`, `-2`, ... suffixed anchors; each one is reported with its `.stab.portability` note.
+/// Methods with neither a cfg condition nor a second definition are omitted.
+pub fn extract_cfg_variants(html: &str, item_name: &str) -> Vec<ItemVariants> {
+    let document = scraper::Html::parse_document(html);
+    let decl_selector = scraper::Selector::parse("pre.item-decl").unwrap();
+    let top_cfg_selector =
+        scraper::Selector::parse("section#main-content > span.item-info .stab.portability")
+            .unwrap();
+    let top_doc_selector = scraper::Selector::parse("details.top-doc > div.docblock").unwrap();
+    let method_selector = scraper::Selector::parse("section.method[id]").unwrap();
+    let header_selector = scraper::Selector::parse("h4.code-header").unwrap();
+    let cfg_selector = scraper::Selector::parse("span.item-info .stab.portability").unwrap();
+    let doc_selector = scraper::Selector::parse(":scope > div.docblock").unwrap();
+
+    let mut out = Vec::new();
+
+    if let Some(decl) = document.select(&decl_selector).next() {
+        out.push(ItemVariants {
+            item: item_name.to_string(),
+            variants: vec![CfgVariant {
+                cfg: document.select(&top_cfg_selector).next().map(element_text),
+                signature: element_text(decl),
+                docs: document
+                    .select(&top_doc_selector)
+                    .next()
+                    .map(|el| crate::markdown::to_markdown(&el.inner_html())),
+            }],
+        });
+    }
+
+    // base anchor (`method.new`) -> variants, in page order
+    let mut methods: Vec<ItemVariants> = Vec::new();
+    for method in document.select(&method_selector) {
+        let id = method.value().attr("id").unwrap_or_default();
+        let base = match id.rsplit_once('-') {
+            Some((base, n)) if n.chars().all(|c| c.is_ascii_digit()) => base,
+            _ => id,
+        };
+        let Some(header) = method.select(&header_selector).next() else {
+            continue;
+        };
+
+        // portability note lives in the method's `<summary>`, docs in the enclosing `<details>`
+        let summary = method
+            .parent()
+            .and_then(scraper::ElementRef::wrap)
+            .filter(|parent| parent.value().name() == "summary");
+        let cfg = summary.and_then(|s| s.select(&cfg_selector).next().map(element_text));
+        let docs = summary
+            .and_then(|s| s.parent())
+            .and_then(scraper::ElementRef::wrap)
+            .and_then(|details| details.select(&doc_selector).next())
+            .map(|el| crate::markdown::to_markdown(&el.inner_html()));
+
+        let variant = CfgVariant {
+            cfg,
+            signature: element_text(header),
+            docs,
+        };
+        match methods.iter_mut().find(|m| m.item == base) {
+            Some(entry) => entry.variants.push(variant),
+            None => methods.push(ItemVariants {
+                item: base.to_string(),
+                variants: vec![variant],
+            }),
+        }
+    }
+
+    out.extend(
+        methods
+            .into_iter()
+            .filter(|m| m.variants.len() > 1 || m.variants.iter().any(|v| v.cfg.is_some())),
+    );
+    out
+}
+
+/// Collect the documented fields of a struct (or union) page from its `Fields` section, in
+/// declaration order. Returns whether the declaration hides private fields alongside them.
+pub fn extract_struct_fields(html: &str) -> (Vec<StructField>, bool) {
+    let document = scraper::Html::parse_document(html);
+    let decl_selector = scraper::Selector::parse("pre.item-decl").unwrap();
+    let field_selector = scraper::Selector::parse("span.structfield[id^='structfield.']").unwrap();
+    let code_selector = scraper::Selector::parse("code").unwrap();
+
+    let decl = document
+        .select(&decl_selector)
+        .next()
+        .map(element_text)
+        .unwrap_or_default();
+    let has_private_fields = decl.contains("/* private fields */");
+
+    let mut fields = Vec::new();
+    for field in document.select(&field_selector) {
+        let id = field.value().attr("id").unwrap_or_default();
+        let name = id.trim_start_matches("structfield.").to_string();
+        let code = field
+            .select(&code_selector)
+            .next()
+            .map(element_text)
+            .unwrap_or_default();
+        let ty = code
+            .strip_prefix(&format!("{}:", name))
+            .unwrap_or(&code)
+            .trim()
+            .to_string();
+        let docs = following_docblock(field);
+        let visibility = field_visibility(&decl, &name);
+        fields.push(StructField {
+            name,
+            ty,
+            docs,
+            visibility,
+        });
+    }
+
+    (fields, has_private_fields)
+}
+
+/// Collect the documented variants of an enum page from its `Variants` section, in declaration
+/// order.
+pub fn extract_enum_variants(html: &str) -> Vec<VariantInfo> {
+    let document = scraper::Html::parse_document(html);
+    let variant_selector = scraper::Selector::parse("section.variant[id^='variant.']").unwrap();
+    let header_selector = scraper::Selector::parse(".code-header").unwrap();
+    let sub_variant_selector = scraper::Selector::parse("div.sub-variant[id]").unwrap();
+    let field_selector = scraper::Selector::parse("span[id]").unwrap();
+    let code_selector = scraper::Selector::parse("code").unwrap();
+
+    let mut out = Vec::new();
+    for variant in document.select(&variant_selector) {
+        let id = variant.value().attr("id").unwrap_or_default();
+        let name = id.trim_start_matches("variant.").to_string();
+        let header = variant
+            .select(&header_selector)
+            .next()
+            .map(element_text)
+            .unwrap_or_default();
+        let rest = header
+            .strip_prefix(name.as_str())
+            .unwrap_or_default()
+            .trim();
+
+        let discriminant = rest.strip_prefix('=').map(|value| value.trim().to_string());
+
+        // documented fields of tuple and struct variants, e.g. `variant.C.field.x`
+        let fields_id = format!("{}.fields", id);
+        let field_prefix = format!("{}.field.", id);
+        let mut fields: Vec<StructField> = document
+            .select(&sub_variant_selector)
+            .filter(|el| el.value().attr("id") == Some(fields_id.as_str()))
+            .flat_map(|sub| sub.select(&field_selector).collect::<Vec<_>>())
+            .filter_map(|field| {
+                let name = field.value().attr("id")?.strip_prefix(&field_prefix)?;
+                let code = field
+                    .select(&code_selector)
+                    .next()
+                    .map(element_text)
+                    .unwrap_or_default();
+                let ty = code.strip_prefix(&format!("{}:", name)).unwrap_or(&code);
+                Some(StructField {
+                    name: name.to_string(),
+                    ty: ty.trim().to_string(),
+                    docs: following_docblock(field),
+                    visibility: "pub".to_string(),
+                })
+            })
+            .collect();
+
+        let kind = if rest.starts_with('(') {
+            if fields.is_empty() {
+                fields = tuple_fields(rest)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, ty)| StructField {
+                        name: i.to_string(),
+                        ty: ty.trim().to_string(),
+                        docs: None,
+                        visibility: "pub".to_string(),
+                    })
+                    .collect();
+            }
+            "tuple"
+        } else if rest.starts_with('{') || !fields.is_empty() {
+            "struct"
+        } else {
+            "unit"
+        };
+
+        out.push(VariantInfo {
+            name,
+            kind: kind.to_string(),
+            discriminant,
+            fields,
+            docs: following_docblock(variant),
+        });
+    }
+    out
+}
+
+/// Collect the impls listed in one of the implementation sections of a type page:
+/// `trait-implementations`, `synthetic-implementations` (auto traits) or
+/// `blanket-implementations`.
+pub fn extract_trait_impls(
+    html: &str,
+    base_dir: &std::path::Path,
+    section_id: &str,
+) -> Vec<TraitImpl> {
+    let document = scraper::Html::parse_document(html);
+    let header_selector = scraper::Selector::parse(&format!(
+        "#{}-list section.impl > h3.code-header",
+        section_id
+    ))
+    .unwrap();
+
+    let mut out = Vec::new();
+    for header in document.select(&header_selector) {
+        // the implemented trait is the last trait link before ` for `; earlier ones are bounds
+        // on the impl's generics
+        let mut trait_link = None;
+        for child in header.children() {
+            match scraper::ElementRef::wrap(child) {
+                Some(el)
+                    if el.value().name() == "a" && el.value().classes().any(|c| c == "trait") =>
+                {
+                    trait_link = Some(el)
+                }
+                Some(_) => {}
+                None => {
+                    if child.value().as_text().is_some_and(|t| t.contains(" for ")) {
+                        break;
+                    }
+                }
+            }
+        }
+        let Some(trait_link) = trait_link else {
+            continue;
+        };
+
+        let fully_qualified_name = trait_link
+            .value()
+            .attr("title")
+            .and_then(|title| title.strip_prefix("trait "))
+            .map(str::to_string);
+        let symbol_path = trait_link
+            .value()
+            .attr("href")
+            .filter(|href| !href.starts_with("http://") && !href.starts_with("https://"))
+            .map(|href| {
+                normalize_rel_path(&base_dir.join(href))
+                    .to_string_lossy()
+                    .replace("\\", "/")
+            });
+
+        out.push(TraitImpl {
+            trait_name: element_text(trait_link),
+            fully_qualified_name,
+            symbol_path,
+            header: code_header_text(header),
+        });
+    }
+    out
+}
+
+/// Collect the methods of each inherent `impl` block on a type page, in page order
+pub fn extract_inherent_methods(html: &str) -> Vec<ImplMethods> {
+    let document = scraper::Html::parse_document(html);
+    let impl_selector = scraper::Selector::parse("#implementations-list section.impl[id]").unwrap();
+    let header_selector = scraper::Selector::parse(".code-header").unwrap();
+    let method_selector = scraper::Selector::parse("section.method[id^='method.']").unwrap();
+    let paragraph_selector = scraper::Selector::parse("p").unwrap();
+
+    let mut out = Vec::new();
+    for section in document.select(&impl_selector) {
+        let Some(header) = section.select(&header_selector).next() else {
+            continue;
+        };
+        // documented items wrap their header in a `<summary>`, the body follows it
+        let Some(items) = toggle_header(section)
+            .next_siblings()
+            .find_map(scraper::ElementRef::wrap)
+            .filter(|el| el.value().classes().any(|c| c == "impl-items"))
+        else {
+            continue;
+        };
+
+        let methods = items
+            .select(&method_selector)
+            .filter_map(|method| {
+                let signature = code_header_text(method.select(&header_selector).next()?);
+                let qualifiers: Vec<&str> = signature
+                    .split_whitespace()
+                    .take_while(|word| *word != "fn")
+                    .collect();
+                let summary = toggle_header(method)
+                    .next_siblings()
+                    .find_map(scraper::ElementRef::wrap)
+                    .filter(|el| el.value().classes().any(|c| c == "docblock"))
+                    .and_then(|docs| docs.select(&paragraph_selector).next())
+                    .map(|p| {
+                        crate::markdown::to_markdown(&p.inner_html())
+                            .trim()
+                            .to_string()
+                    })
+                    .filter(|s| !s.is_empty());
+                Some(MethodInfo {
+                    name: method
+                        .value()
+                        .attr("id")?
+                        .trim_start_matches("method.")
+                        .to_string(),
+                    is_async: qualifiers.contains(&"async"),
+                    is_unsafe: qualifiers.contains(&"unsafe"),
+                    is_const: qualifiers.contains(&"const"),
+                    signature,
+                    summary,
+                })
+            })
+            .collect();
+
+        out.push(ImplMethods {
+            header: code_header_text(header),
+            methods,
+        });
+    }
+    out
+}
+
+/// Collect the `Example`/`Examples` sections of a doc page, see [`section_markdown`]
+pub fn extract_examples(html: &str) -> Vec<ExampleSection> {
+    let document = scraper::Html::parse_document(html);
+    let heading_selector =
+        scraper::Selector::parse(".docblock :is(h1, h2, h3, h4, h5, h6)[id^='example']").unwrap();
+
+    document
+        .select(&heading_selector)
+        .filter(|heading| {
+            let id = heading.value().attr("id").unwrap_or_default();
+            let base = id
+                .rsplit_once('-')
+                .filter(|(_, n)| n.parse::<u32>().is_ok());
+            matches!(base.map_or(id, |(base, _)| base), "example" | "examples")
+        })
+        .map(|heading| ExampleSection {
+            item: member_anchor(heading),
+            markdown: section_markdown(heading),
+        })
+        .collect()
+}
+
+/// Doc sections callers most often need besides the summary, as rustdoc convention spells them
+const USAGE_SECTIONS: &[&str] = &["Panics", "Errors", "Safety"];
+
+/// Collect the `Panics`, `Errors` and `Safety` sections of the docs of one item on a page: the
+/// member with the `member` anchor (e.g. `method.new`), or the page's own item when `None`.
+/// Headings match whatever their level and case; sections a doc doesn't have are left out.
+pub fn extract_doc_sections(
+    html: &str,
+    member: Option<&str>,
+) -> std::collections::BTreeMap<String, String> {
+    let document = scraper::Html::parse_document(html);
+    let heading_selector =
+        scraper::Selector::parse("#main-content .docblock :is(h1, h2, h3, h4, h5, h6)").unwrap();
+
+    let mut sections = std::collections::BTreeMap::<String, String>::new();
+    for heading in document.select(&heading_selector) {
+        let text = heading.text().collect::<String>();
+        let text = text.trim().trim_start_matches('§').trim();
+        let Some(name) = USAGE_SECTIONS
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(text))
+        else {
+            continue;
+        };
+        if member_anchor(heading).as_deref() != member {
+            continue;
+        }
+        let markdown = section_markdown(heading);
+        let section = sections.entry(name.to_string()).or_default();
+        if !section.is_empty() {
+            section.push_str("\n\n");
+        }
+        section.push_str(&markdown);
+    }
+    sections
+}
+
+/// Plain text of every docblock on a page with the anchor of the member it documents (see
+/// [`member_anchor`]), whitespace collapsed
+pub fn extract_docblock_texts(html: &str) -> Vec<(Option<String>, String)> {
+    const INLINE_TAGS: &[&str] = &[
+        "a", "b", "code", "del", "em", "i", "kbd", "span", "strong", "sub", "sup", "wbr",
+    ];
+    let document = scraper::Html::parse_document(html);
+    let docblock_selector = scraper::Selector::parse("#main-content .docblock").unwrap();
+
+    document
+        .select(&docblock_selector)
+        .map(|docblock| {
+            // separate the text of block elements, which `.text()` would run together
+            let mut text = String::new();
+            for node in docblock.descendants() {
+                match node.value() {
+                    scraper::Node::Text(t) => text.push_str(t),
+                    scraper::Node::Element(el) if !INLINE_TAGS.contains(&el.name()) => {
+                        text.push(' ')
+                    }
+                    _ => {}
+                }
+            }
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (member_anchor(docblock), text)
+        })
+        .collect()
+}
+
+/// Markdown of everything following a docblock heading up to the next heading of the same or a
+/// higher level
+fn section_markdown(heading: scraper::ElementRef) -> String {
+    let level = heading_level(heading).unwrap_or(1);
+    let mut body = String::new();
+    for node in heading.next_siblings() {
+        match scraper::ElementRef::wrap(node) {
+            Some(el) if heading_level(el).is_some_and(|l| l <= level) => break,
+            Some(el) => body.push_str(&el.html()),
+            None => {
+                if let Some(text) = node.value().as_text() {
+                    body.push_str(text);
+                }
+            }
+        }
+    }
+    crate::markdown::to_markdown(&body).trim().to_string()
+}
+
+/// Anchor of the member (method, field, variant, ...) whose docs contain `el`, `None` within
+/// the docs of the page's own item. Member docs sit in a `<details>` whose summary is the
+/// member's header.
+fn member_anchor(el: scraper::ElementRef) -> Option<String> {
+    let member_selector = scraper::Selector::parse("summary section[id]").unwrap();
+    el.ancestors()
+        .filter_map(scraper::ElementRef::wrap)
+        .find(|el| el.value().name() == "details")
+        .and_then(|details| details.select(&member_selector).next())
+        .and_then(|section| section.value().attr("id"))
+        .map(str::to_string)
+}
+
+fn heading_level(el: scraper::ElementRef) -> Option<u8> {
+    let name = el.value().name();
+    name.strip_prefix('h')
+        .and_then(|n| n.parse().ok())
+        .filter(|_| name.len() == 2)
+}
+
+/// The `<summary>` wrapping a collapsible item header, or the header itself when it has no docs
+fn toggle_header(section: scraper::ElementRef) -> scraper::ElementRef {
+    section
+        .parent()
+        .and_then(scraper::ElementRef::wrap)
+        .filter(|parent| parent.value().name() == "summary")
+        .unwrap_or(section)
+}
+
+/// Single-line text of a rustdoc `.code-header`; its `where` clause is a nested block that
+/// isn't separated from the preceding text by whitespace
+fn code_header_text(el: scraper::ElementRef) -> String {
+    let mut text = String::new();
+    for node in el.descendants() {
+        if let Some(child) = scraper::ElementRef::wrap(node) {
+            if child.value().classes().any(|c| c == "where") {
+                text.push(' ');
+            }
+        } else if let Some(t) = node.value().as_text() {
+            text.push_str(t);
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Docs rendered right after a field or variant header, undocumented items have none
+fn following_docblock(el: scraper::ElementRef) -> Option<String> {
+    el.next_siblings()
+        .find_map(scraper::ElementRef::wrap)
+        .filter(|el| el.value().classes().any(|c| c == "docblock"))
+        .map(|el| {
+            crate::markdown::to_markdown(&el.inner_html())
+                .trim()
+                .to_string()
+        })
+        .filter(|d| !d.is_empty())
+}
+
+/// Visibility of a field in a struct declaration: `pub name: T` for named fields, the modifier of
+/// the n-th entry of `(pub T, ...)` for tuple fields.
+fn field_visibility(decl: &str, name: &str) -> String {
+    let entry = match name.parse::<usize>() {
+        Ok(index) => tuple_fields(decl).into_iter().nth(index),
+        Err(_) => decl.lines().map(str::trim).find(|line| {
+            line.split(':')
+                .next()
+                .is_some_and(|head| head.split_whitespace().last() == Some(name))
+        }),
+    };
+    let rest = entry.and_then(|entry| entry.trim().strip_prefix("pub"));
+    match rest {
+        Some(scope) if scope.starts_with('(') => {
+            let end = scope.find(')').map_or(scope.len(), |i| i + 1);
+            format!("pub{}", &scope[..end])
+        }
+        Some(rest) if rest.starts_with(char::is_whitespace) => "pub".to_string(),
+        _ => "private".to_string(),
+    }
+}
+
+/// Entries between the parentheses of a tuple struct declaration, split on top-level commas
+fn tuple_fields(decl: &str) -> Vec<&str> {
+    let Some(start) = decl.find('(') else {
+        return Vec::new();
+    };
+    let mut fields = Vec::new();
+    let mut depth = 0;
+    let mut field_start = start + 1;
+    let mut prev = '(';
+    for (i, c) in decl[start..].char_indices().map(|(i, c)| (start + i, c)) {
+        let arrow = prev == '-' && c == '>';
+        prev = c;
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            ')' | '>' | ']' if !arrow => {
+                depth -= 1;
+                if depth == 0 {
+                    fields.push(&decl[field_start..i]);
+                    break;
+                }
+            }
+            ',' if depth == 1 => {
+                fields.push(&decl[field_start..i]);
+                field_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// Whitespace-trimmed text content of an element
+fn element_text(el: scraper::ElementRef) -> String {
+    el.text().collect::<String>().trim().to_string()
+}
+
+/// Run `cargo <subcommand> --message-format=json <args...>` in the current workspace, with
+/// `--offline` in [`offline`] mode, and collect its output
+pub async fn run_json_build(
+    subcommand: &str,
+    args: &[String],
+) -> Result<std::process::Output, CopilotError> {
+    let mut cmd = cargo_command();
+    cmd.arg(subcommand).arg("--message-format=json");
+    // before `args`, which may end with `-- <test args>`
+    if offline() {
+        cmd.arg("--offline");
+    }
+    cmd.args(args);
+    output_in_group(&mut cmd)
+        .await
+        .map_err(|source| CopilotError::Spawn {
+            command: "cargo",
+            source,
+        })
+}
+
+/// Parse the `--message-format=json` output of a cargo build into diagnostics, optionally
+/// keeping only the messages emitted for the given target name
+pub fn parse_diagnostics(stdout: &[u8], target_name: Option<&str>) -> Vec<Diagnostic> {
+    cargo_metadata::Message::parse_stream(stdout)
+        .filter_map(|m| match m {
+            Ok(cargo_metadata::Message::CompilerMessage(msg)) => Some(msg),
+            _ => None,
+        })
+        .filter(|msg| target_name.is_none_or(|name| msg.target.name == name))
+        .map(|msg| {
+            let diag = msg.message;
+            let primary = diag.spans.iter().find(|s| s.is_primary);
+            let suggestions = diag
+                .children
+                .iter()
+                .flat_map(|child| child.spans.iter().map(move |span| (child, span)))
+                .filter_map(|(child, span)| {
+                    Some(Suggestion {
+                        message: child.message.clone(),
+                        file: span.file_name.clone(),
+                        line: span.line_start,
+                        column: span.column_start,
+                        end_line: span.line_end,
+                        end_column: span.column_end,
+                        replacement: span.suggested_replacement.clone()?,
+                        applicability: span.suggestion_applicability.as_ref().and_then(serde_name),
+                    })
+                })
+                .collect();
+            Diagnostic {
+                // reuse cargo's own spelling (`error`, `warning`, `failure-note`, ...)
+                level: serde_name(&diag.level).unwrap_or_default(),
+                message: diag.message,
+                code: diag.code.map(|c| c.code),
+                file: primary.map(|s| s.file_name.clone()),
+                line: primary.map(|s| s.line_start),
+                column: primary.map(|s| s.column_start),
+                rendered: diag.rendered,
+                suggestions,
+            }
+        })
+        .collect()
+}
+
+/// Serialized name of a unit enum variant as cargo spells it in its JSON messages
+fn serde_name<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+}
+
+/// Collect the deprecation notices of the page's item and its methods
+pub fn extract_deprecations(html: &str, symbol_path: &str) -> Vec<DeprecationInfo> {
+    let document = scraper::Html::parse_document(html);
+    let top_selector =
+        scraper::Selector::parse("section#main-content > span.item-info .stab.deprecated").unwrap();
+    let method_selector = scraper::Selector::parse("section.method[id]").unwrap();
+    let stab_selector = scraper::Selector::parse("span.item-info .stab.deprecated").unwrap();
+
+    let mut out = Vec::new();
+    if let Some(stab) = document.select(&top_selector).next() {
+        out.push(parse_deprecation(stab, symbol_path, None));
+    }
+    for method in document.select(&method_selector) {
+        // the deprecation note is a sibling of the method section inside the same `<summary>`
+        let stab = method
+            .parent()
+            .and_then(scraper::ElementRef::wrap)
+            .filter(|parent| parent.value().name() == "summary")
+            .and_then(|summary| summary.select(&stab_selector).next());
+        if let Some(stab) = stab {
+            let anchor = method.value().attr("id").map(String::from);
+            out.push(parse_deprecation(stab, symbol_path, anchor));
+        }
+    }
+    out
+}
+
+/// Parse `Deprecated since 1.2.0: use `bar` instead` into its components
+fn parse_deprecation(
+    stab: scraper::ElementRef,
+    symbol_path: &str,
+    anchor: Option<String>,
+) -> DeprecationInfo {
+    // skip the leading emoji span, the message is in the following span
+    let message_selector = scraper::Selector::parse("span:not(.emoji)").unwrap();
+    let message = stab
+        .select(&message_selector)
+        .next()
+        .map(|el| crate::markdown::to_markdown(&el.inner_html()))
+        .unwrap_or_else(|| element_text(stab));
+    let message = message.trim().trim_start_matches("Deprecated").trim_start();
+
+    let (head, note) = match message.split_once(':') {
+        Some((head, note)) => (head, Some(note.trim().to_string())),
+        None => (message, None),
+    };
+    let since = head
+        .trim()
+        .strip_prefix("since")
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let note = note.filter(|n| !n.is_empty());
+    let replacement = note.as_deref().and_then(deprecation_replacement);
+
+    DeprecationInfo {
+        symbol_path: symbol_path.to_string(),
+        anchor,
+        since,
+        note,
+        replacement,
+    }
+}
+
+/// First inline-code span following a replacement phrase ("use", "replaced by", ...) in a
+/// deprecation note, falling back to the first inline-code span at all
+fn deprecation_replacement(note: &str) -> Option<String> {
+    let code_spans = |s: &str| {
+        s.split('`')
+            .skip(1)
+            .step_by(2)
+            .map(|c| c.trim().to_string())
+            .find(|c| !c.is_empty())
+    };
+    let lower = note.to_lowercase();
+    ["use ", "replaced by", "in favor of", "in favour of", "see "]
+        .iter()
+        .filter_map(|phrase| lower.find(phrase).map(|i| i + phrase.len()))
+        .min()
+        .and_then(|i| code_spans(&note[i..]))
+        .or_else(|| code_spans(note))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `app` depending on `foo = { package = "real-foo", version = "1" }`
+    fn renamed_dep_metadata(resolved: bool) -> cargo_metadata::Metadata {
+        let app = "path+file:///ws/app#0.1.0";
+        let real_foo = "registry+https://github.com/rust-lang/crates.io-index#real-foo@1.2.3";
+        let resolve = resolved.then(|| {
+            serde_json::json!({
+                "root": app,
+                "nodes": [
+                    {
+                        "id": app,
+                        "dependencies": [real_foo],
+                        "deps": [{
+                            "name": "foo",
+                            "pkg": real_foo,
+                            "dep_kinds": [{ "kind": null, "target": null }],
+                        }],
+                        "features": [],
+                    },
+                    { "id": real_foo, "dependencies": [], "deps": [], "features": [] },
+                ],
+            })
+        });
+        serde_json::from_value(serde_json::json!({
+            "packages": [
+                {
+                    "name": "app",
+                    "version": "0.1.0",
+                    "id": app,
+                    "source": null,
+                    "dependencies": [{
+                        "name": "real-foo",
+                        "source": "registry+https://github.com/rust-lang/crates.io-index",
+                        "req": "^1",
+                        "kind": null,
+                        "rename": "foo",
+                        "optional": false,
+                        "uses_default_features": true,
+                        "features": [],
+                        "target": null,
+                        "registry": null,
+                    }],
+                    "targets": [],
+                    "features": {},
+                    "manifest_path": "/ws/app/Cargo.toml",
+                },
+                {
+                    "name": "real-foo",
+                    "version": "1.2.3",
+                    "id": real_foo,
+                    "source": "registry+https://github.com/rust-lang/crates.io-index",
+                    "dependencies": [],
+                    "targets": [],
+                    "features": {},
+                    "manifest_path": "/registry/real-foo-1.2.3/Cargo.toml",
+                },
+            ],
+            "workspace_members": [app],
+            "resolve": resolve,
+            "workspace_root": "/ws/app",
+            "target_directory": "/ws/app/target",
+            "version": 1,
+        }))
+        .expect("valid metadata fixture")
+    }
+
+    fn assert_renamed(metadata: &cargo_metadata::Metadata) {
+        let root = metadata.root_package().expect("root package");
+        let deps = get_dependencies(metadata, root);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].info.crate_id, "real-foo@1.2.3");
+        assert_eq!(deps[0].info.crate_name, "real-foo");
+        assert_eq!(deps[0].rename.as_deref(), Some("foo"));
+    }
+
+    #[test]
+    fn renamed_dependency_from_resolve_graph() {
+        assert_renamed(&renamed_dep_metadata(true));
+    }
+
+    #[test]
+    fn renamed_dependency_from_declarations() {
+        assert_renamed(&renamed_dep_metadata(false));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::cargo::{self, DocTarget, FeatureScope};
+use crate::error::CopilotError;
+
+const DEFAULT_BASE_URL: &str = "https://docs.rs";
+
+/// Fetching docs from docs.rs is opt-in with `CARGO_COPILOT_DOCS_RS=1`, and never happens in
+/// offline mode
+pub fn enabled() -> bool {
+    cargo::env_flag("CARGO_COPILOT_DOCS_RS") && !cargo::offline()
+}
+
+/// docs.rs or a mirror with the same layout set in `CARGO_COPILOT_DOCS_RS_URL`
+fn base_url() -> String {
+    std::env::var("CARGO_COPILOT_DOCS_RS_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Resolve a crate id that isn't in the local dependency graph against docs.rs; a bare name
+/// means the latest release.
+///
+/// Pages are downloaded on first read into `<target dir>/copilot-docs-rs/<name>/<version>`,
+/// laid out like a local `target/doc`, so the doc readers work on them unchanged.
+pub async fn resolve(
+    metadata: &cargo_metadata::Metadata,
+    crate_id: &str,
+) -> Result<DocTarget, CopilotError> {
+    let (name, version) = crate_id.split_once('@').unwrap_or((crate_id, "latest"));
+    // both end up in cache paths and urls
+    if !is_crate_name(name) || !is_version(version) {
+        return Err(CopilotError::InvalidDocsRsCrateId(crate_id.to_string()));
+    }
+    let cache_dir = cargo::target_dir(metadata)
+        .join("copilot-docs-rs")
+        .join(name);
+
+    if version != "latest"
+        && let Some(doc_name) = cached_doc_name(&cache_dir.join(version)).await
+    {
+        return Ok(target(name, version, cache_dir.join(version), doc_name));
+    }
+
+    // docs.rs redirects `/<name>/<version>/` to the docs of the lib target, whose name may
+    // differ from the package name
+    let index = match crate::http::get(&format!("{}/{}/{}/", base_url(), name, version)).await {
+        Ok(index) => index,
+        Err(CopilotError::Http { message, .. }) if message.contains("404") => {
+            return Err(CopilotError::DocsRsNotFound(crate_id.to_string()));
+        }
+        Err(e) => return Err(e),
+    };
+    if !index.body.contains("id=\"main-content\"") {
+        return Err(CopilotError::DocsRsNotFound(crate_id.to_string()));
+    }
+    let doc_name = index
+        .url
+        .trim_end_matches("index.html")
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(name)
+        .to_string();
+    let version = match cargo::doc_version(&index.body) {
+        Some(found) => found.to_string(),
+        None if version != "latest" => version.to_string(),
+        None => return Err(CopilotError::DocsRsNotFound(crate_id.to_string())),
+    };
+    // the served page picks these, keep them from naming paths outside the cache
+    if !is_crate_name(&doc_name) || cargo_metadata::semver::Version::parse(&version).is_err() {
+        return Err(CopilotError::DocsRsNotFound(crate_id.to_string()));
+    }
+
+    let doc_root = cache_dir.join(&version);
+    write_page(&doc_root.join(&doc_name).join("index.html"), &index.body).await?;
+    Ok(target(name, &version, doc_root, doc_name))
+}
+
+fn target(name: &str, version: &str, doc_root: std::path::PathBuf, doc_name: String) -> DocTarget {
+    DocTarget {
+        crate_id: format!("{}@{}", name, version),
+        version: version.to_string(),
+        spec: format!("{}@{}", name, version),
+        doc_name,
+        doc_root,
+        triple: None,
+        source: "crates-io".to_string(),
+        manifest_dir: Default::default(),
+        lockfile: Default::default(),
+        feature_scope: FeatureScope::Unreachable,
+        docs_rs_url: Some(format!("{}/{}/{}", base_url(), name, version)),
+        std_docs: false,
+    }
+}
+
+/// Package or lib target name as cargo allows them: ASCII letters, digits, `-` and `_`
+fn is_crate_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// `latest` or an exact semver version
+fn is_version(version: &str) -> bool {
+    version == "latest" || cargo_metadata::semver::Version::parse(version).is_ok()
+}
+
+/// Lib doc directory of an already downloaded version, the one holding an `index.html`
+async fn cached_doc_name(doc_root: &std::path::Path) -> Option<String> {
+    let mut entries = tokio::fs::read_dir(doc_root).await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name != "src"
+            && tokio::fs::try_exists(entry.path().join("index.html"))
+                .await
+                .unwrap_or(false)
+        {
+            return Some(name);
+        }
+    }
+    None
+}
+
+/// Download the docs.rs page cached at `path` under the target's doc root
+pub async fn fetch_page(
+    target: &DocTarget,
+    path: &std::path::Path,
+) -> Result<String, CopilotError> {
+    let (Some(url), Ok(rel)) = (&target.docs_rs_url, path.strip_prefix(&target.doc_root)) else {
+        return Err(CopilotError::DocFileMissing(path.to_path_buf()));
+    };
+    // `..` in a symbol path would download to, and write, a page outside the doc root
+    if !rel.components().all(|c| {
+        matches!(
+            c,
+            std::path::Component::Normal(_) | std::path::Component::CurDir
+        )
+    }) {
+        return Err(CopilotError::SymbolPathOutsideDocs(
+            rel.display().to_string(),
+        ));
+    }
+    let rel = rel
+        .to_string_lossy()
+        .replace(std::path::MAIN_SEPARATOR, "/");
+    let page = match crate::http::get(&format!("{}/{}", url, rel)).await {
+        Ok(page) => page,
+        // keep reporting a missing page the way a missing local file is reported
+        Err(CopilotError::Http { message, .. }) if message.contains("404") => {
+            return Err(CopilotError::DocFileMissing(path.to_path_buf()));
+        }
+        Err(e) => return Err(e),
+    };
+    write_page(path, &page.body).await?;
+    Ok(page.body)
+}
+
+/// Write through a temporary file so concurrent readers never see a partial page
+async fn write_page(path: &std::path::Path, contents: &str) -> Result<(), CopilotError> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| CopilotError::io("create", dir, e))?;
+    }
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    tokio::fs::write(&tmp, contents)
+        .await
+        .map_err(|e| CopilotError::io("write", &tmp, e))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .map_err(|e| CopilotError::io("write", path, e))
+}
@@ -0,0 +1,257 @@
+use std::path::PathBuf;
+
+/// Failure modes of the cargo helpers and tools. Tools return it as is, `server.rs` renders it
+/// into the MCP error message.
+#[derive(Debug, thiserror::Error)]
+pub enum CopilotError {
+    #[error(
+        "package {crate_id} not found in cargo metadata{}",
+        did_you_mean(suggestions)
+    )]
+    PackageNotFound {
+        crate_id: String,
+        /// closest `name@version` ids, possibly empty
+        suggestions: Vec<String>,
+    },
+
+    #[error(
+        "{crate_id} is not a dependency of the workspace{}",
+        did_you_mean(suggestions)
+    )]
+    NotADependency {
+        crate_id: String,
+        /// closest `name@version` ids, possibly empty
+        suggestions: Vec<String>,
+    },
+
+    #[error(
+        "multiple versions of {name} found in cargo metadata, specify one of: {}",
+        candidates.join(", ")
+    )]
+    AmbiguousPackage {
+        name: String,
+        candidates: Vec<String>,
+    },
+
+    #[error("unknown symbol type {symbol_type}, expected one of: {}", valid.join(", "))]
+    UnknownSymbolType {
+        symbol_type: String,
+        valid: Vec<&'static str>,
+    },
+
+    #[error("unknown section {section}, expected one of: {}", valid.join(", "))]
+    UnknownSection {
+        section: String,
+        valid: Vec<&'static str>,
+    },
+
+    #[error("no root package found")]
+    NoRootPackage,
+
+    #[error(
+        "the workspace has no root package, specify one of its members: {}",
+        members.join(", ")
+    )]
+    WorkspaceMemberRequired { members: Vec<String> },
+
+    #[error(
+        "{package} is not a workspace member, expected one of: {}",
+        members.join(", ")
+    )]
+    NotWorkspaceMember {
+        package: String,
+        members: Vec<String>,
+    },
+
+    #[error("cargo metadata error: {0}")]
+    Metadata(#[from] cargo_metadata::Error),
+
+    #[error("failed to spawn {command}: {source}")]
+    Spawn {
+        command: &'static str,
+        source: std::io::Error,
+    },
+
+    #[error(
+        "cargo doc failed with status: {status}. Ensure the package exists locally{}\n{stderr}",
+        offline_note(*offline)
+    )]
+    CargoDocFailed {
+        status: std::process::ExitStatus,
+        /// `--offline` was passed, so nothing could be fetched from the registry
+        offline: bool,
+        stderr: String,
+    },
+
+    #[error(
+        "target {triple} is not installed (try `rustup target add {triple}`), installed: {}",
+        installed.join(", ")
+    )]
+    TargetNotInstalled {
+        triple: String,
+        installed: Vec<String>,
+    },
+
+    #[error("cargo doc timed out after {}s", .0.as_secs())]
+    CargoDocTimeout(std::time::Duration),
+
+    #[error("cargo check failed with status: {status}\n{stderr}")]
+    CargoCheckFailed {
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+
+    #[error("cargo check timed out after {}s", .0.as_secs())]
+    CargoCheckTimeout(std::time::Duration),
+
+    #[error("cargo {subcommand} failed with status: {status}\n{stderr}")]
+    CargoEditFailed {
+        subcommand: &'static str,
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+
+    #[error("cargo-expand is not installed, install it with `cargo install cargo-expand`")]
+    CargoExpandNotInstalled,
+
+    #[error("cargo expand failed with status: {status}\n{stderr}")]
+    CargoExpandFailed {
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+
+    #[error("invalid dependency {0}, expected `name` or `name@version`")]
+    InvalidDependencySpec(String),
+
+    #[error("precise requires package, the dependency to update to that version")]
+    PreciseWithoutPackage,
+
+    #[error("invalid precise version {0}, expected a version such as 1.2.3")]
+    InvalidPreciseVersion(String),
+
+    #[error(
+        "{name} is not a {section} of {package}, it declares: {}",
+        list_or_none(declared)
+    )]
+    DependencyNotDeclared {
+        name: String,
+        section: &'static str,
+        package: String,
+        declared: Vec<String>,
+    },
+
+    #[error(
+        "standard library docs not found{}, install them with `rustup component add rust-docs`",
+        .0.as_ref().map(|p| format!(" at {}", p.display())).unwrap_or_default()
+    )]
+    RustDocsNotInstalled(Option<PathBuf>),
+
+    #[error("cannot search for this query: {0}")]
+    InvalidGrepQuery(String),
+
+    #[error("server is shutting down")]
+    ShuttingDown,
+
+    #[error("no rustdoc pages found for {0} on docs.rs")]
+    DocsRsNotFound(String),
+
+    #[error(
+        "invalid crate id {0} for docs.rs, expected `name` or `name@version` with a semver version"
+    )]
+    InvalidDocsRsCrateId(String),
+
+    #[error("symbol path {0} points outside the crate's docs")]
+    SymbolPathOutsideDocs(String),
+
+    #[error("request to {url} failed: {message}")]
+    Http { url: String, message: String },
+
+    #[error("crate {0} not found on crates.io")]
+    CrateNotPublished(String),
+
+    #[error("unexpected response from {url}: {source}")]
+    InvalidResponse {
+        url: String,
+        source: serde_json::Error,
+    },
+
+    #[error("no README declared by {0}")]
+    NoReadme(String),
+
+    #[error("README {} declared by {crate_id} not found", path.display())]
+    ReadmeMissing { crate_id: String, path: PathBuf },
+
+    #[error("cursor {0} is not a position in the page, pass a next_cursor returned earlier")]
+    InvalidCursor(usize),
+
+    #[error("invalid resource uri {0}, expected cargo-doc://<crate_id>/<symbol_path>")]
+    InvalidResourceUri(String),
+
+    #[error("invalid resource cursor {0}, pass a nextCursor returned earlier")]
+    InvalidResourceCursor(String),
+
+    #[error("unknown prompt {name}, expected one of: {}", valid.join(", "))]
+    UnknownPrompt {
+        name: String,
+        valid: Vec<&'static str>,
+    },
+
+    #[error("prompt {prompt} requires the {argument} argument")]
+    MissingPromptArgument {
+        prompt: &'static str,
+        argument: &'static str,
+    },
+
+    #[error("documentation file {} not found", .0.display())]
+    DocFileMissing(PathBuf),
+
+    #[error("{selector} not found in {page}")]
+    SelectorMissing {
+        selector: &'static str,
+        page: String,
+    },
+
+    #[error("failed to {action} {}: {source}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("task join error: {0}")]
+    TaskJoin(#[from] tokio::task::JoinError),
+}
+
+impl CopilotError {
+    pub fn io(action: &'static str, path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            action,
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!("; did you mean {}?", suggestions.join(", "))
+    }
+}
+
+fn list_or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+fn offline_note(offline: bool) -> &'static str {
+    if offline {
+        " (offline mode is active, dependencies must already be downloaded)"
+    } else {
+        ""
+    }
+}
@@ -0,0 +1,52 @@
+use crate::error::CopilotError;
+
+const USER_AGENT: &str = concat!(
+    "cargo-copilot/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/pyk/cargo-copilot)"
+);
+const TIMEOUT_SECS: u64 = 30;
+
+/// Body of a successful GET along with the URL it was served from after redirects
+pub struct Response {
+    pub url: String,
+    pub body: String,
+}
+
+/// GET `url` with `curl`, following redirects. HTTP error statuses are reported as
+/// [`CopilotError::Http`].
+pub async fn get(url: &str) -> Result<Response, CopilotError> {
+    let output = tokio::process::Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--location",
+            "--fail",
+            "--compressed",
+        ])
+        .args(["--max-time", &TIMEOUT_SECS.to_string()])
+        .args(["--user-agent", USER_AGENT])
+        // the final URL goes on its own last line, after the body
+        .args(["--write-out", "\n%{url_effective}"])
+        .arg(url)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|source| CopilotError::Spawn {
+            command: "curl",
+            source,
+        })?;
+    if !output.status.success() {
+        return Err(CopilotError::Http {
+            url: url.to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, effective_url) = stdout.rsplit_once('\n').unwrap_or((&stdout, url));
+    Ok(Response {
+        url: effective_url.to_string(),
+        body: body.to_string(),
+    })
+}
@@ -0,0 +1,142 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Metadata, Subscriber, field, span};
+
+/// Filter used when `RUST_LOG` is unset
+const DEFAULT_FILTER: &str = "warn,cargo_copilot=info";
+
+/// Install a subscriber writing events as `LEVEL target: message key=value` lines to stderr;
+/// stdout carries the stdio transport. `RUST_LOG` takes comma separated `level` and
+/// `target=level` directives, the longest matching target wins.
+pub fn init() {
+    let filter = std::env::var("RUST_LOG")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_FILTER.to_string());
+    let subscriber = StderrSubscriber {
+        filter: Filter::parse(&filter),
+        next_span: AtomicU64::new(1),
+    };
+    // only fails when a subscriber is already installed
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+struct Filter {
+    /// level for targets no directive matches
+    default: LevelFilter,
+    /// `(target prefix, level)`, longest prefix first
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// Unparsable directives are skipped
+    fn parse(spec: &str) -> Self {
+        let mut default = LevelFilter::ERROR;
+        let mut targets = Vec::new();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        targets.push((target.trim().replace('-', "_"), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = directive.parse() {
+                        default = level;
+                    }
+                }
+            }
+        }
+        targets.sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Self { default, targets }
+    }
+
+    fn level(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, LevelFilter::max)
+    }
+}
+
+struct StderrSubscriber {
+    filter: Filter,
+    next_span: AtomicU64,
+}
+
+impl Subscriber for StderrSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter.level(metadata.target()) >= *metadata.level()
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.filter.max_level())
+    }
+
+    // spans are not rendered, they only need distinct ids
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let mut line = LineVisitor::default();
+        event.record(&mut line);
+        let _ = writeln!(
+            std::io::stderr().lock(),
+            "{:>5} {}: {}{}",
+            metadata.level(),
+            metadata.target(),
+            line.message,
+            line.fields
+        );
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    /// ` key=value` pairs of the other fields
+    fields: String,
+}
+
+impl field::Visit for LineVisitor {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.fields
+                .push_str(&format!(" {}={}", field.name(), value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message.push_str(&format!("{:?}", value));
+        } else {
+            self.fields
+                .push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
@@ -1,16 +1,128 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use rmcp::{ServiceExt, transport::io::stdio};
 
 mod cargo;
+mod docsrs;
+mod error;
+mod http;
+mod logging;
+mod markdown;
+mod prompts;
+mod registry;
+mod resources;
 mod server;
+mod shutdown;
+mod sysroot;
 mod tools;
 
+const USAGE: &str = "\
+MCP server for Cargo
+
+Usage: cargo copilot [OPTIONS]
+
+Options:
+      --manifest-path <PATH>   Cargo.toml to run cargo against [env: CARGO_COPILOT_MANIFEST_PATH]
+      --offline                only use crates already in the local registry cache [env: CARGO_COPILOT_OFFLINE]
+      --allow-mutations        register the tools editing Cargo.toml and Cargo.lock (cargo_add, cargo_remove, cargo_update) [env: CARGO_COPILOT_ALLOW_MUTATIONS]
+  -h, --help                   print this help
+  -V, --version                print the version";
+
+struct Args {
+    /// `Cargo.toml` to run cargo against instead of the one in the current directory
+    manifest_path: Option<std::path::PathBuf>,
+    /// pass `--offline` to cargo
+    offline: bool,
+    /// register the tools that edit the project
+    allow_mutations: bool,
+}
+
+/// Parse the options in [`USAGE`]; cargo passes the subcommand name as the first argument when
+/// run as `cargo copilot`. `CARGO_COPILOT_MANIFEST_PATH` is used when `--manifest-path` is
+/// absent, `CARGO_COPILOT_ALLOW_MUTATIONS` enables the mutating tools without the flag.
+/// `--help` and `--version` print and exit.
+fn parse_args() -> Result<Args> {
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().is_some_and(|a| a == "copilot") {
+        args.next();
+    }
+
+    let mut manifest_path =
+        std::env::var_os("CARGO_COPILOT_MANIFEST_PATH").filter(|p| !p.is_empty());
+    let mut offline = false;
+    let mut allow_mutations = cargo::env_flag("CARGO_COPILOT_ALLOW_MUTATIONS");
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let mut value = || match inline.clone().or_else(|| args.next()) {
+            Some(value) => Ok(value),
+            None => bail!("missing value for {}", flag),
+        };
+        match flag.as_str() {
+            "--manifest-path" => manifest_path = Some(value()?.into()),
+            "--offline" | "--allow-mutations" if inline.is_some() => {
+                bail!("{} takes no value, pass the flag alone", flag)
+            }
+            "--offline" => offline = true,
+            "--allow-mutations" => allow_mutations = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            "-V" | "--version" => {
+                println!("cargo-copilot {}", env!("CARGO_PKG_VERSION"));
+                std::process::exit(0);
+            }
+            _ => bail!("unknown argument: {}, see --help", flag),
+        }
+    }
+
+    let manifest_path = match manifest_path.map(std::path::PathBuf::from) {
+        Some(path) if !path.is_file() => bail!("manifest path {} does not exist", path.display()),
+        // absolute, so it stays valid whatever directory cargo runs in
+        Some(path) => Some(std::fs::canonicalize(path)?),
+        None => None,
+    };
+    Ok(Args {
+        manifest_path,
+        offline,
+        allow_mutations,
+    })
+}
+
 // npx @modelcontextprotocol/inspector cargo run
 #[tokio::main]
 async fn main() -> Result<()> {
-    eprintln!("Starting cargo-copilot");
-    let service = server::Copilot::new().serve(stdio()).await?;
+    logging::init();
+    let args = parse_args()?;
+    if let Some(path) = args.manifest_path {
+        cargo::set_manifest_path(path);
+    }
+    if args.offline {
+        cargo::set_offline();
+    }
+    let allow_mutations = args.allow_mutations;
+    tracing::info!("starting cargo-copilot");
+    if allow_mutations {
+        tracing::info!("mutating tools enabled");
+    }
 
+    // cancelling stops the session once the running tool calls are drained
+    let session = tokio_util::sync::CancellationToken::new();
+    let service = server::Copilot::new(allow_mutations)
+        .serve_with_ct(stdio(), session.clone())
+        .await?;
+    tokio::spawn(async move {
+        shutdown::signal().await;
+        shutdown::drain().await;
+        session.cancel();
+    });
     service.waiting().await?;
+    // stdin is still open, and tokio waits for the blocking thread reading it on exit
+    if shutdown::is_shutting_down() {
+        std::process::exit(0);
+    }
+
     Ok(())
 }
@@ -0,0 +1,322 @@
+use std::sync::LazyLock;
+
+/// Knobs for [`to_markdown_with`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// keep raw HTML tags that html2md passes through instead of stripping them
+    pub raw_html: bool,
+}
+
+/// Convert rustdoc HTML to markdown with the default [`Options`]
+pub fn to_markdown(html: &str) -> String {
+    to_markdown_with(html, Options::default())
+}
+
+/// Convert rustdoc HTML to markdown
+pub fn to_markdown_with(html: &str, options: Options) -> String {
+    // html2md flattens tables with multi-line or `|`-containing cells, so they are converted
+    // separately and spliced back in place of a placeholder paragraph
+    let mut tables = Vec::new();
+    let html = TABLE.replace_all(html, |caps: &regex::Captures| {
+        tables.push(table_to_markdown(&caps[0], options));
+        format!("<p>{}{}</p>", TABLE_PLACEHOLDER, tables.len() - 1)
+    });
+
+    let mut md = label_code_fences(&html2md::parse_html(&html), &html);
+    // in reverse so `...TABLE1` isn't matched as the prefix of `...TABLE10`
+    for (i, table) in tables.iter().enumerate().rev() {
+        md = md.replace(&format!("{}{}", TABLE_PLACEHOLDER, i), table);
+    }
+    if options.raw_html {
+        md
+    } else {
+        strip_raw_html(&md)
+    }
+}
+
+/// Slice of `md` starting at byte offset `cursor` and at most `max_bytes` long, along with the
+/// offset the next slice starts at when some remains. Slices end before a blank line outside
+/// of code blocks (joining paragraphs, headings and whole code blocks), falling back to a line
+/// end, then whitespace. `None` when `cursor` isn't a character boundary of `md`.
+pub fn slice(md: &str, cursor: usize, max_bytes: usize) -> Option<(&str, Option<usize>)> {
+    let rest = md.get(cursor..)?;
+    if rest.len() <= max_bytes {
+        return Some((rest, None));
+    }
+
+    let mut limit = max_bytes.max(1);
+    while !rest.is_char_boundary(limit) {
+        limit -= 1;
+    }
+    let window = &rest[..limit];
+    let mut paragraph_end = None;
+    let mut in_fence = false;
+    let mut offset = 0;
+    for line in window.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && line.trim().is_empty() && offset > 0 {
+            paragraph_end = Some(offset);
+        }
+        offset += line.len();
+    }
+    let end = paragraph_end
+        .or_else(|| window.rfind('\n').filter(|&i| i > 0))
+        .or_else(|| window.rfind(char::is_whitespace).filter(|&i| i > 0))
+        .unwrap_or(limit);
+
+    let next = rest[end..]
+        .find(|c: char| !c.is_whitespace())
+        .map(|skip| cursor + end + skip);
+    Some((&rest[..end], next))
+}
+
+/// Remove HTML tags html2md left in the markdown (e.g. `<details>`, `<sup>`, `<img ...>`),
+/// keeping their text. Code blocks and inline code are left untouched.
+fn strip_raw_html(md: &str) -> String {
+    let mut out = String::with_capacity(md.len());
+    let mut in_fence = false;
+    for line in md.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || line.trim_start().starts_with("```") {
+            out.push_str(line);
+            continue;
+        }
+        // backtick-delimited spans alternate between text and inline code
+        for (i, part) in line.split('`').enumerate() {
+            if i > 0 {
+                out.push('`');
+            }
+            if i % 2 == 1 {
+                out.push_str(part);
+            } else {
+                out.push_str(&strip_tags(part));
+            }
+        }
+    }
+    out
+}
+
+static TABLE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"(?s)<table\b.*?</table>").unwrap());
+const TABLE_PLACEHOLDER: &str = "CARGOCOPILOTTABLE";
+
+/// Render an HTML table as a GitHub-flavored markdown table. The `<thead>` row is the header,
+/// or the first row when there is none.
+fn table_to_markdown(html: &str, options: Options) -> String {
+    let fragment = scraper::Html::parse_fragment(html);
+    let row_selector = scraper::Selector::parse("tr").unwrap();
+
+    let mut header = None;
+    let mut rows = Vec::new();
+    for row in fragment.select(&row_selector) {
+        let cells: Vec<scraper::ElementRef> = row
+            .children()
+            .filter_map(scraper::ElementRef::wrap)
+            .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+            .collect();
+        let in_thead = row
+            .ancestors()
+            .filter_map(scraper::ElementRef::wrap)
+            .any(|el| el.value().name() == "thead");
+        if in_thead && header.is_none() {
+            header = Some(cells);
+        } else {
+            rows.push(cells);
+        }
+    }
+    let header = match header {
+        Some(header) => header,
+        None if !rows.is_empty() => rows.remove(0),
+        None => return String::new(),
+    };
+
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .chain([header.len()])
+        .max()
+        .unwrap_or(0);
+    let render_row = |cells: &[scraper::ElementRef]| {
+        let mut line = String::from("|");
+        for i in 0..columns {
+            let text = cells.get(i).map_or_else(String::new, |cell| {
+                // GFM cells are a single line and `|` ends a cell even inside code spans
+                to_markdown_with(&cell.inner_html(), options)
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .replace('|', "\\|")
+            });
+            line.push(' ');
+            line.push_str(&text);
+            line.push_str(" |");
+        }
+        line
+    };
+
+    let mut out = vec![render_row(&header)];
+    let separator: Vec<&str> = (0..columns)
+        .map(|i| match header.get(i).map(cell_alignment) {
+            Some(Some("left")) => ":---",
+            Some(Some("center")) => ":---:",
+            Some(Some("right")) => "---:",
+            _ => "---",
+        })
+        .collect();
+    out.push(format!("| {} |", separator.join(" | ")));
+    out.extend(rows.iter().map(|row| render_row(row)));
+    out.join("\n")
+}
+
+/// `align` attribute or `text-align` style of a header cell, as pulldown-cmark renders them
+fn cell_alignment<'a>(cell: &scraper::ElementRef<'a>) -> Option<&'a str> {
+    let value = cell.value();
+    let align = value.attr("align").or_else(|| {
+        value
+            .attr("style")?
+            .split(';')
+            .find_map(|decl| {
+                decl.split_once(':')
+                    .filter(|(k, _)| k.trim() == "text-align")
+            })
+            .map(|(_, v)| v.trim())
+    })?;
+    ["left", "center", "right"]
+        .into_iter()
+        .find(|a| *a == align)
+}
+
+static PRE_TAG: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"<pre\b([^>]*)>").unwrap());
+static ATTR_CLASS: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r#"class\s*=\s*"([^"]*)""#).unwrap());
+
+/// html2md renders every `<pre>` as a bare fence. Tag the opening fences, in document order,
+/// with the language rustdoc put on the block: `rust` for Rust code and item declarations,
+/// `language-<lang>` for other fenced blocks, `text` when there is none.
+fn label_code_fences(md: &str, html: &str) -> String {
+    let mut languages = PRE_TAG.captures_iter(html).map(|caps| {
+        let classes = ATTR_CLASS
+            .captures(caps.get(1).map_or("", |m| m.as_str()))
+            .and_then(|c| c.get(1))
+            .map_or("", |m| m.as_str());
+        let mut classes = classes.split_whitespace();
+        if classes.clone().any(|c| c == "rust") {
+            "rust"
+        } else {
+            classes
+                .find_map(|c| c.strip_prefix("language-"))
+                .filter(|lang| !lang.is_empty())
+                .unwrap_or("text")
+        }
+    });
+
+    let mut out = String::with_capacity(md.len());
+    let mut in_fence = false;
+    for line in md.split_inclusive('\n') {
+        if line.trim() == "```" {
+            if !in_fence {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                out.push_str(indent);
+                out.push_str("```");
+                out.push_str(languages.next().unwrap_or("text"));
+                out.push_str(&line[indent.len() + 3..]);
+            } else {
+                out.push_str(line);
+            }
+            in_fence = !in_fence;
+            continue;
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+static TAG: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"</?([a-zA-Z][a-zA-Z0-9-]*)([^<>]*)>").unwrap());
+static ATTR_SRC: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r#"src\s*=\s*"([^"]*)""#).unwrap());
+static ATTR_ALT: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r#"alt\s*=\s*"([^"]*)""#).unwrap());
+
+/// Tags that separate text when rendered
+const BLOCK_TAGS: &[&str] = &["br", "details", "div", "p", "summary"];
+
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in TAG.captures_iter(text) {
+        let m = caps.get(0).unwrap();
+        // html2md escapes literal angle brackets as `\<`, those aren't tags
+        if text[..m.start()].ends_with('\\') {
+            continue;
+        }
+        out.push_str(&text[last..m.start()]);
+        let name = caps[1].to_ascii_lowercase();
+        let separates = !out.trim().is_empty() && !text[m.end()..].trim().is_empty();
+        if BLOCK_TAGS.contains(&name.as_str()) && separates && !out.ends_with(' ') {
+            // keep `<summary>More</summary>hidden` from collapsing into `Morehidden`
+            out.push(' ');
+        } else if name == "img" {
+            let attr = |re: &regex::Regex| {
+                re.captures(&caps[2])
+                    .map(|c| c[1].to_string())
+                    .unwrap_or_default()
+            };
+            let src = attr(&ATTR_SRC);
+            if !src.is_empty() {
+                out.push_str(&format!("![{}]({})", attr(&ATTR_ALT), src));
+            }
+        }
+        last = m.end();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_img_becomes_markdown_image() {
+        let md =
+            strip_raw_html(r#"Logo: <img alt="logo" src="https://example.com/logo.png"> here"#);
+        assert_eq!(md, "Logo: ![logo](https://example.com/logo.png) here");
+    }
+
+    #[test]
+    fn wrappers_are_stripped_and_text_kept() {
+        let md = to_markdown(
+            "<div class=\"warning\"><p>Careful</p></div>\
+             <details><summary>More</summary><p>hidden text</p></details>",
+        );
+        assert!(!md.contains('<'), "tags left in {:?}", md);
+        assert!(md.contains("Careful"));
+        assert!(md.contains("More"));
+        assert!(md.contains("hidden text"));
+        assert!(!md.contains("Morehidden"));
+    }
+
+    #[test]
+    fn code_is_left_alone() {
+        let md =
+            strip_raw_html("use `<div>` here\n```rust\nlet s = \"<img src=\\\"x\\\">\";\n```\n");
+        assert_eq!(
+            md,
+            "use `<div>` here\n```rust\nlet s = \"<img src=\\\"x\\\">\";\n```\n"
+        );
+    }
+
+    #[test]
+    fn raw_html_keeps_tags() {
+        let html = "<details><summary>More</summary><p>hidden text</p></details>";
+        let md = to_markdown_with(html, Options { raw_html: true });
+        assert!(md.contains("<details>"), "{:?}", md);
+        assert!(md.contains("<summary>"), "{:?}", md);
+        assert!(!to_markdown(html).contains("<details>"));
+    }
+}
@@ -0,0 +1,124 @@
+use rmcp::model::{
+    GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole,
+};
+
+use crate::error::CopilotError;
+
+struct PromptSpec {
+    name: &'static str,
+    description: &'static str,
+    /// `(name, description)`, all required
+    arguments: &'static [(&'static str, &'static str)],
+    /// message text with `{argument}` placeholders
+    template: &'static str,
+}
+
+const CRATE_ID: (&str, &str) = (
+    "crate_id",
+    "crate id in the form `name@version` or just `name`",
+);
+
+const PROMPTS: &[PromptSpec] = &[
+    PromptSpec {
+        name: "explain_crate",
+        description: "Explain how to use a crate from its local documentation",
+        arguments: &[CRATE_ID],
+        template: "\
+Explain how to use the `{crate_id}` crate, based only on its documentation.
+
+1. Call `cargo_doc_overview` with crate_id `{crate_id}` to read the crate level docs.
+2. Call `cargo_doc_index` with crate_id `{crate_id}` to see its modules and main items.
+3. Call `cargo_doc_get` on the few types and functions most users start with, and \
+`cargo_doc_examples` on them when the overview has no example.
+
+Summarize what the crate is for, its core types and the usual entry points, then show a \
+minimal example using the items you read. Say when something is not covered by the docs \
+instead of guessing.",
+    },
+    PromptSpec {
+        name: "find_api",
+        description: "Find the right API of a crate for a task",
+        arguments: &[CRATE_ID, ("task", "what the code needs to do")],
+        template: "\
+Find the API of the `{crate_id}` crate to use for this task: {task}
+
+1. Call `cargo_doc_search` with crate_id `{crate_id}` and a few keywords from the task, \
+setting `include_description` to also match descriptions.
+2. When the names don't say enough, call `cargo_doc_grep` with crate_id `{crate_id}` to find \
+where the concept is discussed.
+3. Call `cargo_doc_get` with `mode` `signature` on the candidates, then read the full page \
+and `cargo_doc_sections` of the best one.
+4. Verify the usage with `cargo_snippet_check` before answering.
+
+Answer with the item to use, its signature, the required imports and a short example that \
+passed `cargo_snippet_check`.",
+    },
+    PromptSpec {
+        name: "review_dependency",
+        description: "Review a dependency's version, features and deprecations",
+        arguments: &[CRATE_ID],
+        template: "\
+Review how the project depends on `{crate_id}`.
+
+1. Call `cargo_package_info` with crate_id `{crate_id}` for its version, license and \
+`rust-version`.
+2. Call `cargo_features` with crate_id `{crate_id}` to see which features are enabled and \
+whether the defaults are needed.
+3. Call `crates_io_versions` with the crate name to check for newer releases or a yanked \
+version.
+4. Call `cargo_deprecated` with crate_id `{crate_id}` to list deprecated items.
+
+Report whether the dependency is up to date, features that could be disabled and deprecated \
+items the project may be using, with their replacements.",
+    },
+];
+
+/// Every prompt with its arguments
+pub fn list() -> Vec<Prompt> {
+    PROMPTS
+        .iter()
+        .map(|spec| {
+            let arguments = spec
+                .arguments
+                .iter()
+                .map(|(name, description)| PromptArgument {
+                    name: name.to_string(),
+                    title: None,
+                    description: Some(description.to_string()),
+                    required: Some(true),
+                })
+                .collect();
+            Prompt::new(spec.name, Some(spec.description), Some(arguments))
+        })
+        .collect()
+}
+
+/// Render the prompt `name` with `arguments` into a single user message
+pub fn get(name: &str, arguments: Option<&JsonObject>) -> Result<GetPromptResult, CopilotError> {
+    let spec = PROMPTS
+        .iter()
+        .find(|spec| spec.name == name)
+        .ok_or_else(|| CopilotError::UnknownPrompt {
+            name: name.to_string(),
+            valid: PROMPTS.iter().map(|spec| spec.name).collect(),
+        })?;
+
+    let mut text = spec.template.to_string();
+    for (argument, _) in spec.arguments {
+        let value = arguments
+            .and_then(|args| args.get(*argument))
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| CopilotError::MissingPromptArgument {
+                prompt: spec.name,
+                argument,
+            })?;
+        text = text.replace(&format!("{{{}}}", argument), value);
+    }
+
+    Ok(GetPromptResult {
+        description: Some(spec.description.to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    })
+}
@@ -0,0 +1,143 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::CopilotError;
+
+const API_URL: &str = "https://crates.io/api/v1";
+/// crates.io asks API clients to make at most one request per second
+const MIN_REQUEST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Time of the last crates.io request, shared by every session of the process
+static LAST_REQUEST: tokio::sync::Mutex<Option<tokio::time::Instant>> =
+    tokio::sync::Mutex::const_new(None);
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CrateSummary {
+    pub name: String,
+    /// highest published version, pre-releases included
+    pub max_version: String,
+    pub description: Option<String>,
+    /// all-time download count
+    pub downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    crates: Vec<CrateSummary>,
+    meta: SearchMeta,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchMeta {
+    total: u64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VersionInfo {
+    pub version: String,
+    pub yanked: bool,
+    /// RFC 3339 timestamp of the release
+    pub published_at: String,
+    /// the highest version that isn't yanked, preferring stable releases over pre-releases
+    pub is_latest: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionsResponse {
+    versions: Vec<RawVersion>,
+    #[serde(default)]
+    meta: VersionsMeta,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawVersion {
+    num: String,
+    yanked: bool,
+    created_at: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VersionsMeta {
+    /// query string of the next page, absent on the last one
+    next_page: Option<String>,
+}
+
+/// Search crates.io by name and keywords, returning the total number of matches along with
+/// the first `per_page` crates in crates.io relevance order
+pub async fn search(query: &str, per_page: u32) -> Result<(u64, Vec<CrateSummary>), CopilotError> {
+    let url = format!(
+        "{}/crates?q={}&per_page={}",
+        API_URL,
+        percent_encode(query),
+        per_page
+    );
+    let body = get_throttled(&url).await?;
+    let resp: SearchResponse = serde_json::from_str(&body)
+        .map_err(|source| CopilotError::InvalidResponse { url, source })?;
+    Ok((resp.meta.total, resp.crates))
+}
+
+/// GET `url`, waiting first if the previous request was less than [`MIN_REQUEST_INTERVAL`] ago
+async fn get_throttled(url: &str) -> Result<String, CopilotError> {
+    let mut last = LAST_REQUEST.lock().await;
+    if let Some(last) = *last {
+        tokio::time::sleep_until(last + MIN_REQUEST_INTERVAL).await;
+    }
+    *last = Some(tokio::time::Instant::now());
+    drop(last);
+    Ok(crate::http::get(url).await?.body)
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Every published version of a crate, highest first
+pub async fn versions(name: &str) -> Result<Vec<VersionInfo>, CopilotError> {
+    let base = format!("{}/crates/{}/versions", API_URL, percent_encode(name));
+    let mut url = format!("{}?per_page=100", base);
+    let mut raw = Vec::new();
+    loop {
+        let body = match get_throttled(&url).await {
+            Ok(body) => body,
+            Err(CopilotError::Http { message, .. }) if message.contains("404") => {
+                return Err(CopilotError::CrateNotPublished(name.to_string()));
+            }
+            Err(e) => return Err(e),
+        };
+        let page: VersionsResponse = serde_json::from_str(&body)
+            .map_err(|source| CopilotError::InvalidResponse { url, source })?;
+        raw.extend(page.versions);
+        match page.meta.next_page {
+            Some(next) => url = format!("{}{}", base, next),
+            None => break,
+        }
+    }
+
+    let parse = |num: &str| cargo_metadata::semver::Version::parse(num).ok();
+    raw.sort_by_key(|v| std::cmp::Reverse(parse(&v.num)));
+    let latest = raw
+        .iter()
+        .filter(|v| !v.yanked)
+        .find(|v| parse(&v.num).is_some_and(|num| num.pre.is_empty()))
+        .or_else(|| raw.iter().find(|v| !v.yanked))
+        .map(|v| v.num.clone());
+
+    Ok(raw
+        .into_iter()
+        .map(|v| VersionInfo {
+            is_latest: latest.as_ref() == Some(&v.num),
+            version: v.num,
+            yanked: v.yanked,
+            published_at: v.created_at,
+        })
+        .collect())
+}
@@ -0,0 +1,92 @@
+use rmcp::model::{AnnotateAble, RawResource, Resource};
+
+use crate::cargo;
+use crate::error::CopilotError;
+use crate::tools::cargo_doc_get;
+
+/// Scheme of the doc page resources: `cargo-doc://<crate_id>/<symbol_path>`
+const SCHEME: &str = "cargo-doc://";
+const MIME_TYPE: &str = "text/markdown";
+
+/// Resources of one documented crate, the page at `cursor` (an index into the crates whose docs
+/// are already generated, sorted by crate id), and the cursor of the next crate if any. Listing
+/// never runs `cargo doc`.
+pub async fn list(cursor: Option<&str>) -> Result<(Vec<Resource>, Option<String>), CopilotError> {
+    let start = match cursor {
+        Some(c) => c
+            .parse::<usize>()
+            .map_err(|_| CopilotError::InvalidResourceCursor(c.to_string()))?,
+        None => 0,
+    };
+    let targets = documented_targets().await?;
+    let Some(target) = targets.get(start) else {
+        return Ok((Vec::new(), None));
+    };
+
+    let html = cargo::read_doc_index_html(target).await?;
+    let symbols = cargo::extract_symbols(&html, target).await?;
+    let mut resources = vec![
+        RawResource {
+            description: Some(format!("crate documentation of {}", target.crate_id)),
+            mime_type: Some(MIME_TYPE.to_string()),
+            ..RawResource::new(uri(&target.crate_id, "index.html"), target.doc_name.clone())
+        }
+        .no_annotation(),
+    ];
+    let mut seen = std::collections::HashSet::new();
+    for symbol in symbols {
+        // links into other crates' docs belong to those crates
+        if symbol.symbol_path.starts_with("../") || !seen.insert(symbol.symbol_path.clone()) {
+            continue;
+        }
+        resources.push(
+            RawResource {
+                description: symbol.symbol_description,
+                mime_type: Some(MIME_TYPE.to_string()),
+                ..RawResource::new(
+                    uri(&target.crate_id, &symbol.symbol_path),
+                    symbol.fully_qualified_name,
+                )
+            }
+            .no_annotation(),
+        );
+    }
+
+    let next = (start + 1 < targets.len()).then(|| (start + 1).to_string());
+    Ok((resources, next))
+}
+
+/// Markdown of the page behind a `cargo-doc://` URI, as `cargo_doc_get` renders it
+pub async fn read(uri: &str) -> Result<String, CopilotError> {
+    let (crate_id, symbol_path) = uri
+        .strip_prefix(SCHEME)
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(crate_id, path)| !crate_id.is_empty() && !path.is_empty())
+        .ok_or_else(|| CopilotError::InvalidResourceUri(uri.to_string()))?;
+
+    let target = cargo::resolve_doc_target(crate_id).await?;
+    cargo::doc(&target).await?;
+    cargo_doc_get::page_markdown(
+        &target,
+        symbol_path,
+        Default::default(),
+        cargo_doc_get::Mode::Full,
+    )
+    .await
+}
+
+fn uri(crate_id: &str, symbol_path: &str) -> String {
+    format!("{}{}/{}", SCHEME, crate_id, symbol_path)
+}
+
+/// Already documented packages of the dependency graph, sorted by crate id
+async fn documented_targets() -> Result<Vec<cargo::DocTarget>, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let mut targets: Vec<cargo::DocTarget> = cargo::scan_doc_dirs(&metadata)
+        .await?
+        .into_iter()
+        .filter_map(|dir| dir.target)
+        .collect();
+    targets.sort_by(|a, b| a.crate_id.cmp(&b.crate_id));
+    Ok(targets)
+}
@@ -1,14 +1,68 @@
 use rmcp::{
-    Json, ServerHandler,
+    ErrorData, Json, RoleServer, ServerHandler,
     handler::server::{tool::ToolRouter, wrapper::Parameters},
-    model::{ServerCapabilities, ServerInfo},
+    model::{
+        CallToolResult, Content, GetPromptRequestParam, GetPromptResult, ListPromptsResult,
+        ListResourcesResult, PaginatedRequestParam, ReadResourceRequestParam, ReadResourceResult,
+        ResourceContents, ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
     tool, tool_handler, tool_router,
 };
+use tokio_util::sync::CancellationToken;
 
+use crate::error::CopilotError;
+use crate::prompts;
+use crate::resources;
+use crate::shutdown;
+use crate::tools::cargo_add;
+use crate::tools::cargo_api_fingerprint;
+use crate::tools::cargo_check;
+use crate::tools::cargo_clippy;
+use crate::tools::cargo_crate_meta;
+use crate::tools::cargo_crate_tags;
 use crate::tools::cargo_dependencies;
+use crate::tools::cargo_depgraph_dot;
+use crate::tools::cargo_depgraph_json;
+use crate::tools::cargo_deprecated;
+use crate::tools::cargo_deps_stats;
+use crate::tools::cargo_doc_attributes;
+use crate::tools::cargo_doc_examples;
 use crate::tools::cargo_doc_get;
+use crate::tools::cargo_doc_get_batch;
+use crate::tools::cargo_doc_grep;
 use crate::tools::cargo_doc_index;
+use crate::tools::cargo_doc_list;
 use crate::tools::cargo_doc_overview;
+use crate::tools::cargo_doc_overview_structured;
+use crate::tools::cargo_doc_search;
+use crate::tools::cargo_doc_sections;
+use crate::tools::cargo_doc_variants;
+use crate::tools::cargo_duplicate_deps;
+use crate::tools::cargo_enum_variants;
+use crate::tools::cargo_expand;
+use crate::tools::cargo_features;
+use crate::tools::cargo_no_std;
+use crate::tools::cargo_outdated_check;
+use crate::tools::cargo_package_info;
+use crate::tools::cargo_readme;
+use crate::tools::cargo_reexports;
+use crate::tools::cargo_remove;
+use crate::tools::cargo_snippet_check;
+use crate::tools::cargo_source_get;
+use crate::tools::cargo_struct_fields;
+use crate::tools::cargo_test;
+use crate::tools::cargo_tree;
+use crate::tools::cargo_type_impls;
+use crate::tools::cargo_type_methods;
+use crate::tools::cargo_update;
+use crate::tools::cargo_why;
+use crate::tools::cargo_workspace_members;
+use crate::tools::crates_io_search;
+use crate::tools::crates_io_versions;
+
+/// Tools editing the project, only registered when the server runs with `--allow-mutations`
+const MUTATING_TOOLS: &[&str] = &["cargo_add", "cargo_remove", "cargo_update"];
 
 #[derive(Debug, Default, Clone)]
 pub struct Copilot {
@@ -17,18 +71,181 @@ pub struct Copilot {
 
 #[tool_router]
 impl Copilot {
-    pub fn new() -> Self {
-        Self {
-            tool_router: Self::tool_router(),
+    pub fn new(allow_mutations: bool) -> Self {
+        let mut tool_router = Self::tool_router();
+        if !allow_mutations {
+            for name in MUTATING_TOOLS {
+                tool_router.remove_route(name);
+            }
         }
+        Self { tool_router }
     }
 
     #[tool(
         name = "cargo_dependencies",
-        description = "List all available dependencies as crate ids (name@version)"
+        description = "List all available dependencies as crate ids (name@version), of the root package or a given workspace member; a virtual workspace lists the dependencies of all members"
+    )]
+    async fn cargo_dependencies(
+        &self,
+        Parameters(req): Parameters<cargo_dependencies::Request>,
+    ) -> Result<Json<cargo_dependencies::Response>, String> {
+        let resp = in_flight(cargo_dependencies::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_workspace_members",
+        description = "List the packages of the workspace with their manifest paths, marking the root package"
+    )]
+    async fn cargo_workspace_members(
+        &self,
+    ) -> Result<Json<cargo_workspace_members::Response>, String> {
+        let resp = in_flight(cargo_workspace_members::run())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_doc_list",
+        description = "List the crates whose docs are already generated under target/doc, so cargo_doc_* calls on them don't trigger a build"
+    )]
+    async fn cargo_doc_list(&self) -> Result<Json<cargo_doc_list::Response>, String> {
+        let resp = in_flight(cargo_doc_list::run())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "crates_io_search",
+        description = "Search crates.io for crates matching a query, returning each crate's latest version, description and download count. Needs network access"
+    )]
+    async fn crates_io_search(
+        &self,
+        Parameters(req): Parameters<crates_io_search::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<crates_io_search::Response>, String> {
+        let resp = cancellable(ct, crates_io_search::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "crates_io_versions",
+        description = "List the published versions of a crate on crates.io, highest first, with yanked status, publish date and the latest non-yanked release marked. Needs network access"
+    )]
+    async fn crates_io_versions(
+        &self,
+        Parameters(req): Parameters<crates_io_versions::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<crates_io_versions::Response>, String> {
+        let resp = cancellable(ct, crates_io_versions::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_outdated_check",
+        description = "For each direct dependency of a package, compare the locked version with the latest crates.io release and the latest one its version requirement accepts. Git and path dependencies are skipped with a note. Needs network access"
+    )]
+    async fn cargo_outdated_check(
+        &self,
+        Parameters(req): Parameters<cargo_outdated_check::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_outdated_check::Response>, String> {
+        let resp = cancellable(ct, cargo_outdated_check::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_why",
+        description = "Explain why a crate is in the dependency graph: the dependency paths from the root package down to it as lists of `name@version`, like `cargo tree -i`, capped at `max_paths`"
+    )]
+    async fn cargo_why(
+        &self,
+        Parameters(req): Parameters<cargo_why::Request>,
+    ) -> Result<Json<cargo_why::Response>, String> {
+        let resp = in_flight(cargo_why::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_duplicate_deps",
+        description = "List crates resolved at more than one version, with the dependents pulling in each version"
+    )]
+    async fn cargo_duplicate_deps(&self) -> Result<Json<cargo_duplicate_deps::Response>, String> {
+        let resp = in_flight(cargo_duplicate_deps::run())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_deps_stats",
+        description = "Overview of the dependency footprint: resolved package count, direct dependencies by kind and the depth of the resolve graph"
+    )]
+    async fn cargo_deps_stats(&self) -> Result<Json<cargo_deps_stats::Response>, String> {
+        let resp = in_flight(cargo_deps_stats::run())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_depgraph_dot",
+        description = "Export the resolved dependency graph as a Graphviz DOT document with one `name@version` node per package, optionally only what the root package reaches and with dependency kinds as edge labels"
     )]
-    async fn cargo_dependencies(&self) -> Result<Json<cargo_dependencies::Response>, String> {
-        let resp = cargo_dependencies::run().await?;
+    async fn cargo_depgraph_dot(
+        &self,
+        Parameters(req): Parameters<cargo_depgraph_dot::Request>,
+    ) -> Result<String, String> {
+        in_flight(cargo_depgraph_dot::run(&req))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        name = "cargo_depgraph_json",
+        description = "Export the resolved dependency graph as JSON: `name@version` nodes marking the root, directed edges with their dependency kinds, and the node and edge counts"
+    )]
+    async fn cargo_depgraph_json(
+        &self,
+        Parameters(req): Parameters<cargo_depgraph_json::Request>,
+    ) -> Result<Json<cargo_depgraph_json::Response>, String> {
+        let resp = in_flight(cargo_depgraph_json::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_tree",
+        description = "Show the resolved dependency tree of the root package (or a given crate), optionally limited to `max_depth` levels"
+    )]
+    async fn cargo_tree(
+        &self,
+        Parameters(req): Parameters<cargo_tree::Request>,
+    ) -> Result<Json<cargo_tree::Response>, String> {
+        let resp = in_flight(cargo_tree::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_readme",
+        description = "Return the README a crate declares in its manifest, as markdown when it is Markdown or HTML"
+    )]
+    async fn cargo_readme(
+        &self,
+        Parameters(req): Parameters<cargo_readme::Request>,
+    ) -> Result<Json<cargo_readme::Response>, String> {
+        let resp = in_flight(cargo_readme::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
         Ok(Json(resp))
     }
 
@@ -39,32 +256,453 @@ impl Copilot {
     async fn cargo_doc_overview(
         &self,
         Parameters(req): Parameters<cargo_doc_overview::Request>,
+        ct: CancellationToken,
     ) -> Result<String, String> {
-        cargo_doc_overview::run(&req).await
+        cancellable(ct, cargo_doc_overview::run(&req)).await
+    }
+
+    #[tool(
+        name = "cargo_doc_overview_structured",
+        description = "Like cargo_doc_overview, with the crate level docs split at their top level headings into `{ heading, markdown }` sections, the content before the first heading being `intro`"
+    )]
+    async fn cargo_doc_overview_structured(
+        &self,
+        Parameters(req): Parameters<cargo_doc_overview_structured::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_overview_structured::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_overview_structured::run(&req)).await?;
+        Ok(Json(resp))
     }
 
     #[tool(
         name = "cargo_doc_index",
-        description = "List symbols (modules, macros, structs, enums, functions, types) from a crate's generated docs"
+        description = "List symbols (modules, macros, structs, enums, functions, types, traits, constants, statics) from a crate's generated docs. Filter with `symbol_types` and a `query` substring, page large crates with `offset` and `limit`"
     )]
     async fn cargo_doc_index(
         &self,
         Parameters(req): Parameters<cargo_doc_index::Request>,
+        ct: CancellationToken,
     ) -> Result<Json<cargo_doc_index::Response>, String> {
-        let resp = cargo_doc_index::run(&req).await?;
+        let resp = cancellable(ct, cargo_doc_index::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_doc_search",
+        description = "Fuzzy search a crate's documented symbols by name (case and snake_case/CamelCase insensitive), best matches first"
+    )]
+    async fn cargo_doc_search(
+        &self,
+        Parameters(req): Parameters<cargo_doc_search::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_search::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_search::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_doc_grep",
+        description = "Full-text search through the prose and code of every doc page of a crate, case insensitive, returning the symbol path and a snippet per match"
+    )]
+    async fn cargo_doc_grep(
+        &self,
+        Parameters(req): Parameters<cargo_doc_grep::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_grep::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_grep::run(&req)).await?;
         Ok(Json(resp))
     }
 
     #[tool(
         name = "cargo_doc_get",
-        description = "Get full documentation page for a symbol as markdown"
+        description = "Get full documentation page for a symbol as markdown, or as the original rustdoc HTML with `format: html`. With `max_bytes`, long pages come in slices and a second `next_cursor: N` block tells the `cursor` to continue from"
     )]
     async fn cargo_doc_get(
         &self,
         Parameters(req): Parameters<cargo_doc_get::Request>,
-    ) -> Result<String, String> {
-        let resp = cargo_doc_get::run(&req).await?;
-        Ok(resp)
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, ErrorData> {
+        // several content blocks, which `Result<String, String>` can't express
+        let resp = match cancellable(ct, cargo_doc_get::run(&req)).await {
+            Ok(resp) => resp,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+        let mut content = vec![Content::text(resp.content)];
+        if let Some(cursor) = resp.next_cursor {
+            content.push(Content::text(format!("next_cursor: {}", cursor)));
+        }
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        name = "cargo_doc_get_batch",
+        description = "Get the documentation pages of several symbols of a crate at once as markdown, keyed by symbol path; a page that can't be read reports its own error"
+    )]
+    async fn cargo_doc_get_batch(
+        &self,
+        Parameters(req): Parameters<cargo_doc_get_batch::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_get_batch::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_get_batch::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_doc_examples",
+        description = "Extract only the Examples sections of a symbol's docs (the item's and its members'), with code blocks as fenced markdown"
+    )]
+    async fn cargo_doc_examples(
+        &self,
+        Parameters(req): Parameters<cargo_doc_examples::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_examples::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_examples::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_doc_sections",
+        description = "Extract the Panics, Errors and Safety sections of a symbol's docs (or of a member, e.g. `struct.Foo#method.new`) as markdown"
+    )]
+    async fn cargo_doc_sections(
+        &self,
+        Parameters(req): Parameters<cargo_doc_sections::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_sections::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_sections::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_source_get",
+        description = "Get the Rust source of a symbol by following its `[src]` link in the generated docs"
+    )]
+    async fn cargo_source_get(
+        &self,
+        Parameters(req): Parameters<cargo_source_get::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_source_get::Response>, String> {
+        let resp = cancellable(ct, cargo_source_get::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_doc_attributes",
+        description = "List attributes (must_use, repr, non_exhaustive, deprecated, ...) of a symbol and its methods"
+    )]
+    async fn cargo_doc_attributes(
+        &self,
+        Parameters(req): Parameters<cargo_doc_attributes::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_attributes::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_attributes::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_struct_fields",
+        description = "List a struct's fields with their types, docs and visibility"
+    )]
+    async fn cargo_struct_fields(
+        &self,
+        Parameters(req): Parameters<cargo_struct_fields::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_struct_fields::Response>, String> {
+        let resp = cancellable(ct, cargo_struct_fields::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_enum_variants",
+        description = "List an enum's variants with their kind (unit/tuple/struct), data, discriminant and docs"
+    )]
+    async fn cargo_enum_variants(
+        &self,
+        Parameters(req): Parameters<cargo_enum_variants::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_enum_variants::Response>, String> {
+        let resp = cancellable(ct, cargo_enum_variants::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_type_impls",
+        description = "List the traits a struct or enum implements, with auto trait and blanket impls grouped separately"
+    )]
+    async fn cargo_type_impls(
+        &self,
+        Parameters(req): Parameters<cargo_type_impls::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_type_impls::Response>, String> {
+        let resp = cancellable(ct, cargo_type_impls::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_type_methods",
+        description = "List the inherent methods of a struct or enum, grouped by impl block, with signatures, doc summaries and async/unsafe/const flags"
+    )]
+    async fn cargo_type_methods(
+        &self,
+        Parameters(req): Parameters<cargo_type_methods::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_type_methods::Response>, String> {
+        let resp = cancellable(ct, cargo_type_methods::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_api_fingerprint",
+        description = "Compute a stable hash of a crate's public API (symbol paths, types and signatures)"
+    )]
+    async fn cargo_api_fingerprint(
+        &self,
+        Parameters(req): Parameters<cargo_api_fingerprint::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_api_fingerprint::Response>, String> {
+        let resp = cancellable(ct, cargo_api_fingerprint::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_doc_variants",
+        description = "List the cfg-gated variants (e.g. unix vs windows) of a symbol and its methods with their cfg conditions"
+    )]
+    async fn cargo_doc_variants(
+        &self,
+        Parameters(req): Parameters<cargo_doc_variants::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_doc_variants::Response>, String> {
+        let resp = cancellable(ct, cargo_doc_variants::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_snippet_check",
+        description = "Compile a Rust snippet against a dependency in a throwaway crate with `cargo check` and return the diagnostics"
+    )]
+    async fn cargo_snippet_check(
+        &self,
+        Parameters(req): Parameters<cargo_snippet_check::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_snippet_check::Response>, String> {
+        let resp = cancellable(ct, cargo_snippet_check::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_check",
+        description = "Run `cargo check` on the workspace (or one package) and return structured compiler diagnostics"
+    )]
+    async fn cargo_check(
+        &self,
+        Parameters(req): Parameters<cargo_check::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_check::Response>, String> {
+        let resp = cancellable(ct, cargo_check::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_clippy",
+        description = "Run `cargo clippy` and return lint diagnostics with suggested fixes, optionally filtered to specific lints"
+    )]
+    async fn cargo_clippy(
+        &self,
+        Parameters(req): Parameters<cargo_clippy::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_clippy::Response>, String> {
+        let resp = cancellable(ct, cargo_clippy::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_test",
+        description = "Run `cargo test` and return a pass/fail summary with the output of failing tests"
+    )]
+    async fn cargo_test(
+        &self,
+        Parameters(req): Parameters<cargo_test::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_test::Response>, String> {
+        let resp = cancellable(ct, cargo_test::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_expand",
+        description = "Show the source of a workspace package, or of one `item` path in it, after macro expansion, using the `cargo expand` subcommand (install it with `cargo install cargo-expand`)"
+    )]
+    async fn cargo_expand(
+        &self,
+        Parameters(req): Parameters<cargo_expand::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_expand::Response>, String> {
+        let resp = cancellable(ct, cargo_expand::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_add",
+        description = "Add a dependency to Cargo.toml with `cargo add`, optionally with features or as a dev-dependency, returning the manifest lines it changed. Only available when the server runs with --allow-mutations"
+    )]
+    async fn cargo_add(
+        &self,
+        Parameters(req): Parameters<cargo_add::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_add::Response>, String> {
+        let resp = cancellable(ct, cargo_add::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_remove",
+        description = "Remove a dependency from Cargo.toml with `cargo remove`, returning the manifest lines it changed. Only available when the server runs with --allow-mutations"
+    )]
+    async fn cargo_remove(
+        &self,
+        Parameters(req): Parameters<cargo_remove::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_remove::Response>, String> {
+        let resp = cancellable(ct, cargo_remove::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_update",
+        description = "Update Cargo.lock with `cargo update`, optionally for one dependency and to a `precise` version, returning the locked versions that changed. `dry_run` reports them without writing the lockfile. Only available when the server runs with --allow-mutations"
+    )]
+    async fn cargo_update(
+        &self,
+        Parameters(req): Parameters<cargo_update::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_update::Response>, String> {
+        let resp = cancellable(ct, cargo_update::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_crate_meta",
+        description = "Get a crate's MSRV (rust-version), edition, authors, keywords, categories, repository and homepage"
+    )]
+    async fn cargo_crate_meta(
+        &self,
+        Parameters(req): Parameters<cargo_crate_meta::Request>,
+    ) -> Result<Json<cargo_crate_meta::Response>, String> {
+        let resp = in_flight(cargo_crate_meta::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_crate_tags",
+        description = "Get the keywords and crates.io categories declared by a crate"
+    )]
+    async fn cargo_crate_tags(
+        &self,
+        Parameters(req): Parameters<cargo_crate_tags::Request>,
+    ) -> Result<Json<cargo_crate_tags::Response>, String> {
+        let resp = in_flight(cargo_crate_tags::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_reexports",
+        description = "List a crate's `pub use` re-exports with their public path and canonical defining path"
+    )]
+    async fn cargo_reexports(
+        &self,
+        Parameters(req): Parameters<cargo_reexports::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_reexports::Response>, String> {
+        let resp = cancellable(ct, cargo_reexports::run(&req)).await?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_package_info",
+        description = "Get the manifest metadata (version, features, license, edition, rust-version, dependencies, ...) of one package"
+    )]
+    async fn cargo_package_info(
+        &self,
+        Parameters(req): Parameters<cargo_package_info::Request>,
+    ) -> Result<Json<cargo_package_info::Response>, String> {
+        let resp = in_flight(cargo_package_info::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_features",
+        description = "List a crate's Cargo features with what each one enables, which are default and which are enabled in the current build"
+    )]
+    async fn cargo_features(
+        &self,
+        Parameters(req): Parameters<cargo_features::Request>,
+    ) -> Result<Json<cargo_features::Response>, String> {
+        let resp = in_flight(cargo_features::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_no_std",
+        description = "Heuristically check whether a dependency can be used without std"
+    )]
+    async fn cargo_no_std(
+        &self,
+        Parameters(req): Parameters<cargo_no_std::Request>,
+    ) -> Result<Json<cargo_no_std::Response>, String> {
+        let resp = in_flight(cargo_no_std::run(&req))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(resp))
+    }
+
+    #[tool(
+        name = "cargo_deprecated",
+        description = "List deprecated items and methods of a crate with their deprecation note and suggested replacement"
+    )]
+    async fn cargo_deprecated(
+        &self,
+        Parameters(req): Parameters<cargo_deprecated::Request>,
+        ct: CancellationToken,
+    ) -> Result<Json<cargo_deprecated::Response>, String> {
+        let resp = cancellable(ct, cargo_deprecated::run(&req)).await?;
+        Ok(Json(resp))
+    }
+}
+
+/// Run a tool the client can't cancel, counted as in flight so shutdown waits for the cargo
+/// processes it spawns. Refused once the server shuts down.
+async fn in_flight<T>(
+    tool: impl Future<Output = Result<T, CopilotError>>,
+) -> Result<T, CopilotError> {
+    let Some(_in_flight) = shutdown::InFlight::start() else {
+        return Err(CopilotError::ShuttingDown);
+    };
+    tool.await
+}
+
+/// Run a tool until it completes or the client cancels the request. Dropping the tool's future
+/// kills the cargo or curl process it is waiting on (they are all spawned `kill_on_drop`, and
+/// tokio reaps them) and releases its doc build permit. Refused once the server shuts down,
+/// which waits for the calls already running.
+async fn cancellable<T>(
+    ct: CancellationToken,
+    tool: impl Future<Output = Result<T, CopilotError>>,
+) -> Result<T, String> {
+    let Some(_in_flight) = shutdown::InFlight::start() else {
+        return Err(CopilotError::ShuttingDown.to_string());
+    };
+    tokio::select! {
+        resp = tool => resp.map_err(|e| e.to_string()),
+        _ = ct.cancelled() => Err("request cancelled".to_string()),
     }
 }
 
@@ -73,8 +711,78 @@ impl ServerHandler for Copilot {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             instructions: Some("MCP server for Cargo".into()),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
             ..Default::default()
         }
     }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let cursor = request.and_then(|r| r.cursor);
+        let (resources, next_cursor) = in_flight(resources::list(cursor.as_deref()))
+            .await
+            .map_err(request_error)?;
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor,
+            meta: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let text = in_flight(resources::read(&request.uri))
+            .await
+            .map_err(request_error)?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some("text/markdown".to_string()),
+                text,
+                meta: None,
+            }],
+        })
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(ListPromptsResult::with_all_items(prompts::list()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        prompts::get(&request.name, request.arguments.as_ref()).map_err(request_error)
+    }
+}
+
+/// Map a failed resource or prompt request to the matching JSON-RPC error
+fn request_error(e: CopilotError) -> ErrorData {
+    match e {
+        CopilotError::InvalidResourceUri(_)
+        | CopilotError::InvalidResourceCursor(_)
+        | CopilotError::UnknownPrompt { .. }
+        | CopilotError::MissingPromptArgument { .. } => {
+            ErrorData::invalid_params(e.to_string(), None)
+        }
+        CopilotError::PackageNotFound { .. } | CopilotError::DocFileMissing(_) => {
+            ErrorData::resource_not_found(e.to_string(), None)
+        }
+        e => ErrorData::internal_error(e.to_string(), None),
+    }
 }
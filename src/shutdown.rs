@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::cargo;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// A tool call or resource request running cargo or curl, counted until dropped so [`drain`] can wait for it
+pub struct InFlight(());
+
+impl InFlight {
+    /// `None` once the server is shutting down, new calls are refused
+    pub fn start() -> Option<Self> {
+        if is_shutting_down() {
+            return None;
+        }
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        Some(Self(()))
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Whether a shutdown signal was received
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Resolves on the first SIGINT (Ctrl-C) or, on unix, SIGTERM
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        let mut term =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).ok();
+        let term = async {
+            match term.as_mut() {
+                Some(term) => {
+                    term.recv().await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => tracing::info!("received SIGINT, shutting down"),
+            _ = term => tracing::info!("received SIGTERM, shutting down"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        tracing::info!("received Ctrl-C, shutting down");
+    }
+}
+
+/// Refuse new tool calls, wait for the running ones to finish for up to
+/// `CARGO_COPILOT_SHUTDOWN_TIMEOUT_SECS` (30s by default), then kill the cargo processes left
+pub async fn drain() {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    let timeout = shutdown_timeout();
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    let running = IN_FLIGHT.load(Ordering::SeqCst);
+    if running > 0 {
+        tracing::info!(
+            running,
+            "waiting up to {}s for running tool calls",
+            timeout.as_secs()
+        );
+    }
+    while IN_FLIGHT.load(Ordering::SeqCst) > 0 && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    let killed = cargo::kill_running();
+    if killed > 0 {
+        tracing::warn!(
+            killed,
+            "killed cargo processes still running after the timeout"
+        );
+    }
+    tracing::info!("shutdown complete");
+}
+
+fn shutdown_timeout() -> std::time::Duration {
+    let secs = std::env::var("CARGO_COPILOT_SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    std::time::Duration::from_secs(secs)
+}
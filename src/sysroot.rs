@@ -0,0 +1,55 @@
+use crate::cargo::{self, DocTarget, FeatureScope};
+use crate::error::CopilotError;
+
+/// Crates documented by the rustup `rust-docs` component instead of `cargo doc`
+pub const STD_CRATES: &[&str] = &["std", "core", "alloc"];
+
+/// Whether `crate_id` (`name` or `name@version`) names a standard library crate
+pub fn is_std_crate(crate_id: &str) -> bool {
+    let name = crate_id.split_once('@').map_or(crate_id, |(name, _)| name);
+    STD_CRATES.contains(&name)
+}
+
+/// Standard library docs shipped with the toolchain under
+/// `<sysroot>/share/doc/rust/html`, laid out like a local `target/doc`, so the doc readers work
+/// on them unchanged. A version other than the toolchain's is not found.
+pub async fn resolve(crate_id: &str) -> Result<DocTarget, CopilotError> {
+    let (name, version) = match crate_id.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (crate_id, None),
+    };
+    let doc_root = cargo::sysroot()
+        .await
+        .ok_or(CopilotError::RustDocsNotInstalled(None))?
+        .join("share")
+        .join("doc")
+        .join("rust")
+        .join("html");
+    let index = doc_root.join(name).join("index.html");
+    let html = tokio::fs::read_to_string(&index)
+        .await
+        .map_err(|_| CopilotError::RustDocsNotInstalled(Some(index.clone())))?;
+
+    let installed = cargo::doc_version(&html).unwrap_or("unknown").to_string();
+    if version.is_some_and(|v| v != installed) {
+        return Err(CopilotError::PackageNotFound {
+            crate_id: crate_id.to_string(),
+            suggestions: vec![format!("{}@{}", name, installed)],
+        });
+    }
+
+    Ok(DocTarget {
+        crate_id: format!("{}@{}", name, installed),
+        version: installed.clone(),
+        spec: name.to_string(),
+        doc_name: name.to_string(),
+        doc_root,
+        triple: None,
+        source: "rust-docs".to_string(),
+        manifest_dir: Default::default(),
+        lockfile: Default::default(),
+        feature_scope: FeatureScope::Unreachable,
+        docs_rs_url: None,
+        std_docs: true,
+    })
+}
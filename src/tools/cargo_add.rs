@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// dependency to add as `name` or `name@version`, the version being a requirement like `1.2`
+    pub crate_id: String,
+    /// features of the dependency to enable (`--features`)
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// add it to `[dev-dependencies]` (`--dev`)
+    #[serde(default)]
+    pub dev: bool,
+    /// workspace member (`name` or `name@version`) whose manifest is edited, defaults to the
+    /// root package
+    pub package: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    #[serde(flatten)]
+    pub change: cargo::ManifestChange,
+}
+
+/// Runs `cargo add`; only registered with `--allow-mutations`
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let crate_id = req.crate_id.trim();
+    // anything else would reach cargo as a flag or a path
+    if crate_id.is_empty() || crate_id.starts_with('-') || crate_id.contains(['/', '\\']) {
+        return Err(CopilotError::InvalidDependencySpec(req.crate_id.clone()));
+    }
+
+    let mut args = vec![crate_id.to_string()];
+    if !req.features.is_empty() {
+        args.push("--features".to_string());
+        args.push(req.features.join(","));
+    }
+    if req.dev {
+        args.push("--dev".to_string());
+    }
+    let change = cargo::edit_manifest("add", &args, req.package.as_deref()).await?;
+    Ok(Response { change })
+}
@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// hex encoded hash of the sorted `(symbol_path, symbol_type, signature)` tuples
+    pub fingerprint: String,
+    /// number of symbols that went into the hash
+    pub symbol_count: usize,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+    let html = cargo::read_doc_index_html(&target).await?;
+    let symbols = cargo::extract_symbols(&html, &target).await?;
+
+    let mut entries = Vec::with_capacity(symbols.len());
+    let mut pending = symbols.into_iter();
+    let mut tasks = tokio::task::JoinSet::new();
+    loop {
+        while tasks.len() < cargo::extract_concurrency()
+            && let Some(symbol) = pending.next()
+        {
+            let target = target.clone();
+            tasks.spawn(async move {
+                // modules have no declaration block, an empty signature keeps them in the hash
+                let signature =
+                    match cargo::read_doc_html_by_rel_path(&target, &symbol.symbol_path).await {
+                        Ok(page) => cargo::parse_blocking(move || cargo::extract_signature(&page))
+                            .await?
+                            .unwrap_or_default(),
+                        Err(_) => String::new(),
+                    };
+                Ok::<_, CopilotError>((symbol.symbol_path, symbol.symbol_type, signature))
+            });
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        entries.push(joined??);
+    }
+    // pages complete in arbitrary order, the hash is over the sorted entries
+    entries.sort();
+    entries.dedup();
+
+    // fields and entries are separated by bytes that can't appear in rendered text
+    let mut buf = Vec::new();
+    for (path, kind, signature) in &entries {
+        for field in [path, kind, signature] {
+            buf.extend_from_slice(field.as_bytes());
+            buf.push(0x1f);
+        }
+        buf.push(0x1e);
+    }
+
+    Ok(Response {
+        fingerprint: format!("{:016x}", cargo::fnv1a64(&buf)),
+        symbol_count: entries.len(),
+    })
+}
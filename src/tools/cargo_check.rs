@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// only check this workspace package (`--package`), the whole workspace otherwise
+    pub package: Option<String>,
+    /// also check tests, examples and benches (`--all-targets`)
+    #[serde(default)]
+    pub all_targets: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// whether `cargo check` succeeded
+    pub success: bool,
+    /// compiler diagnostics in the order rustc emitted them
+    pub diagnostics: Vec<cargo::Diagnostic>,
+    /// cargo's stderr when it failed without reporting a compile error (bad manifest,
+    /// resolution failure, ...), none otherwise
+    pub cargo_error: Option<String>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let mut args = cargo::package_args(req.package.as_deref()).await?;
+    if req.all_targets {
+        args.push("--all-targets".to_string());
+    }
+
+    let output = cargo::run_json_build("check", &args).await?;
+    let diagnostics = cargo::parse_diagnostics(&output.stdout, None);
+
+    let success = output.status.success();
+    let compile_failed = diagnostics.iter().any(|d| d.level == "error");
+    let cargo_error = (!success && !compile_failed)
+        .then(|| String::from_utf8_lossy(&output.stderr).trim().to_string());
+
+    Ok(Response {
+        success,
+        diagnostics,
+        cargo_error,
+    })
+}
@@ -0,0 +1,78 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// only lint this workspace package (`--package`), the whole workspace otherwise
+    pub package: Option<String>,
+    /// also lint tests, examples and benches (`--all-targets`)
+    #[serde(default)]
+    pub all_targets: bool,
+    /// only report these lints, e.g. `clippy::needless_return` or `needless_return`; compile
+    /// errors are always reported
+    #[serde(default)]
+    pub lints: Vec<String>,
+    /// report warnings with the `error` level, as `-D warnings` would
+    #[serde(default)]
+    pub deny_warnings: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// whether clippy succeeded, and with `deny_warnings` reported no warnings
+    pub success: bool,
+    /// clippy and compiler diagnostics in the order they were emitted, lint fixes are listed
+    /// in `suggestions`
+    pub diagnostics: Vec<cargo::Diagnostic>,
+    /// cargo's stderr when it failed without reporting a compile error, none otherwise
+    pub cargo_error: Option<String>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let mut args = cargo::package_args(req.package.as_deref()).await?;
+    if req.all_targets {
+        args.push("--all-targets".to_string());
+    }
+
+    let output = cargo::run_json_build("clippy", &args).await?;
+    let mut diagnostics = cargo::parse_diagnostics(&output.stdout, None);
+
+    let compile_failed = diagnostics.iter().any(|d| d.level == "error");
+    let cargo_error = (!output.status.success() && !compile_failed)
+        .then(|| String::from_utf8_lossy(&output.stderr).trim().to_string());
+
+    if !req.lints.is_empty() {
+        diagnostics.retain(|d| match d.code.as_deref() {
+            Some(code) if is_lint(code) => req.lints.iter().any(|l| lint_matches(l, code)),
+            _ => d.level == "error",
+        });
+    }
+    if req.deny_warnings {
+        for diag in &mut diagnostics {
+            if diag.level == "warning" {
+                diag.level = "error".to_string();
+            }
+        }
+    }
+
+    let success = output.status.success() && !diagnostics.iter().any(|d| d.level == "error");
+    Ok(Response {
+        success,
+        diagnostics,
+        cargo_error,
+    })
+}
+
+/// Lint names like `clippy::needless_return` or `unused_variables`, as opposed to `E0308`
+fn is_lint(code: &str) -> bool {
+    !(code.starts_with('E') && code[1..].chars().all(|c| c.is_ascii_digit()))
+}
+
+/// `needless_return` and `clippy::needless_return` both match `clippy::needless_return`
+fn lint_matches(filter: &str, code: &str) -> bool {
+    let filter = filter.trim().replace('-', "_");
+    code == filter || code.rsplit("::").next() == Some(filter.as_str())
+}
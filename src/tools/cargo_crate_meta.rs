@@ -0,0 +1,44 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version`
+    pub crate_id: String,
+    /// minimum supported Rust version (`package.rust-version`), `null` when not declared
+    pub rust_version: Option<String>,
+    /// Rust edition, e.g. `2021`
+    pub edition: String,
+    pub authors: Vec<String>,
+    pub keywords: Vec<String>,
+    /// crates.io category slugs
+    pub categories: Vec<String>,
+    pub repository: Option<String>,
+    pub homepage: Option<String>,
+}
+
+/// Only reads `cargo metadata`, no doc build
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_package(&metadata, &req.crate_id)?;
+
+    Ok(Response {
+        crate_id: format!("{}@{}", pkg.name, pkg.version),
+        rust_version: pkg.rust_version.as_ref().map(|v| v.to_string()),
+        edition: pkg.edition.to_string(),
+        authors: pkg.authors.clone(),
+        keywords: pkg.keywords.clone(),
+        categories: pkg.categories.clone(),
+        repository: pkg.repository.clone(),
+        homepage: pkg.homepage.clone(),
+    })
+}
@@ -0,0 +1,34 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version`
+    pub crate_id: String,
+    /// `package.keywords` from the crate's manifest
+    pub keywords: Vec<String>,
+    /// `package.categories` from the crate's manifest (crates.io category slugs)
+    pub categories: Vec<String>,
+}
+
+/// `cargo metadata` reports the published manifest of registry packages, so the tags match
+/// what crates.io shows for the locked version without a network round-trip
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_package(&metadata, &req.crate_id)?;
+
+    Ok(Response {
+        crate_id: format!("{}@{}", pkg.name, pkg.version),
+        keywords: pkg.keywords.clone(),
+        categories: pkg.categories.clone(),
+    })
+}
@@ -1,21 +1,43 @@
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// workspace member (`name` or `name@version`) whose dependencies are listed, defaults to
+    /// the root package, or to every member in a virtual workspace
+    pub package: Option<String>,
+}
 
 /// Response for `cargo_dependencies` tool
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct Response {
-    pub crates: Vec<cargo::CrateInfo>,
+    pub crates: Vec<cargo::DependencyInfo>,
+    /// the workspace has no root package, so `crates` merges the dependencies of all members
+    pub workspace_wide: bool,
 }
 
 /// Logic for the `cargo_dependencies` tool (self-contained)
-pub async fn run() -> Result<Response, String> {
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
     let metadata = cargo::get_metadata().await?;
-    let root = metadata
-        .root_package()
-        .ok_or_else(|| "no root package found".to_string())?;
+    if req.package.is_none() && metadata.root_package().is_none() {
+        let crates = metadata
+            .workspace_packages()
+            .into_iter()
+            .flat_map(|pkg| cargo::get_dependencies(&metadata, pkg))
+            .collect();
+        return Ok(Response {
+            crates: cargo::unique_sorted_crates(crates),
+            workspace_wide: true,
+        });
+    }
 
-    let crates = cargo::get_dependencies(&metadata, root);
-    Ok(Response { crates })
+    let pkg = cargo::find_workspace_member(&metadata, req.package.as_deref())?;
+    let crates = cargo::get_dependencies(&metadata, pkg);
+    Ok(Response {
+        crates,
+        workspace_wide: false,
+    })
 }
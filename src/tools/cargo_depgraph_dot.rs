@@ -0,0 +1,77 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// only packages reachable from the root package (every workspace member in a virtual
+    /// workspace), the whole resolve graph otherwise
+    #[serde(default)]
+    pub root_only: bool,
+    /// label edges with their dependency kinds, normal|dev|build
+    #[serde(default)]
+    pub edge_labels: bool,
+}
+
+/// The resolve graph as a Graphviz `digraph`, one `name@version` node per package and one edge
+/// per dependency, sorted so the output is stable
+pub async fn run(req: &Request) -> Result<String, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let nodes = cargo::resolve_nodes(&metadata);
+    let crate_ids: HashMap<&cargo_metadata::PackageId, String> = metadata
+        .packages
+        .iter()
+        .map(|p| (&p.id, format!("{}@{}", p.name, p.version)))
+        .collect();
+
+    let included: HashSet<&cargo_metadata::PackageId> = if req.root_only {
+        cargo::reachable(cargo::graph_roots(&metadata), &nodes)
+    } else {
+        nodes.keys().copied().collect()
+    };
+
+    let crate_id = |id: &cargo_metadata::PackageId| {
+        crate_ids
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.repr.clone())
+    };
+    let mut node_lines = BTreeSet::new();
+    let mut edge_lines = BTreeSet::new();
+    for id in &included {
+        node_lines.insert(format!("    {};", quote(&crate_id(id))));
+        let Some(node) = nodes.get(id) else {
+            continue;
+        };
+        for dep in &node.deps {
+            let mut edge = format!(
+                "    {} -> {}",
+                quote(&crate_id(id)),
+                quote(&crate_id(&dep.pkg))
+            );
+            if req.edge_labels {
+                let kinds = cargo::dependency_kinds(dep.dep_kinds.iter().map(|k| k.kind));
+                edge.push_str(&format!(" [label={}]", quote(&kinds.join(","))));
+            }
+            edge.push(';');
+            edge_lines.insert(edge);
+        }
+    }
+
+    let mut dot = String::from("digraph dependencies {\n");
+    for line in node_lines.iter().chain(&edge_lines) {
+        dot.push_str(line);
+        dot.push('\n');
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
+
+/// DOT quoted id, escaping backslashes and double quotes
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
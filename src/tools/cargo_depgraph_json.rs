@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// only packages reachable from the root package (every workspace member in a virtual
+    /// workspace), the whole resolve graph otherwise
+    #[serde(default)]
+    pub root_only: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// sorted by `id`
+    pub nodes: Vec<GraphNode>,
+    /// sorted by `from`, then `to`
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GraphNode {
+    /// id formatted as `name@version`
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// the root package, or a member of a virtual workspace
+    pub is_root: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GraphEdge {
+    /// `id` of the dependent
+    pub from: String,
+    /// `id` of the dependency
+    pub to: String,
+    /// every kind the dependency is declared with: normal|dev|build
+    pub kinds: Vec<String>,
+}
+
+/// The resolve graph as nodes and directed edges, one edge per dependent and dependency whatever
+/// the number of kinds it is declared with
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let nodes = cargo::resolve_nodes(&metadata);
+    let roots: HashSet<&cargo_metadata::PackageId> =
+        cargo::graph_roots(&metadata).into_iter().collect();
+    let included: HashSet<&cargo_metadata::PackageId> = if req.root_only {
+        cargo::reachable(roots.iter().copied(), &nodes)
+    } else {
+        nodes.keys().copied().collect()
+    };
+
+    let crate_id = |pkg: &cargo_metadata::Package| format!("{}@{}", pkg.name, pkg.version);
+    let mut graph_nodes: Vec<GraphNode> = metadata
+        .packages
+        .iter()
+        .filter(|pkg| included.contains(&pkg.id))
+        .map(|pkg| GraphNode {
+            id: crate_id(pkg),
+            name: pkg.name.clone(),
+            version: pkg.version.to_string(),
+            is_root: roots.contains(&pkg.id),
+        })
+        .collect();
+    graph_nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut edges: Vec<GraphEdge> = Vec::new();
+    for pkg in metadata
+        .packages
+        .iter()
+        .filter(|pkg| included.contains(&pkg.id))
+    {
+        let Some(node) = nodes.get(&pkg.id) else {
+            continue;
+        };
+        for dep in &node.deps {
+            edges.push(GraphEdge {
+                from: crate_id(pkg),
+                to: cargo::format_dep_info(dep, &metadata).crate_id,
+                kinds: cargo::dependency_kinds(dep.dep_kinds.iter().map(|k| k.kind)),
+            });
+        }
+    }
+    edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+    // a package depending on another under several target tables yields several node deps
+    edges.dedup_by(|dup, kept| {
+        if (&dup.from, &dup.to) != (&kept.from, &kept.to) {
+            return false;
+        }
+        kept.kinds.append(&mut dup.kinds);
+        kept.kinds.sort();
+        kept.kinds.dedup();
+        true
+    });
+
+    Ok(Response {
+        node_count: graph_nodes.len(),
+        edge_count: edges.len(),
+        nodes: graph_nodes,
+        edges,
+    })
+}
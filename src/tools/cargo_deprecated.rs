@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// deprecated items and methods, empty when the crate has none
+    pub deprecated: Vec<cargo::DeprecationInfo>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+    let html = cargo::read_doc_index_html(&target).await?;
+    let symbols = cargo::extract_symbols(&html, &target).await?;
+
+    let mut seen = std::collections::HashSet::new();
+    let mut deprecated = Vec::new();
+    for symbol in symbols {
+        if !seen.insert(symbol.symbol_path.clone()) {
+            continue;
+        }
+        // ignore missing symbol page
+        if let Ok(page) = cargo::read_doc_html_by_rel_path(&target, &symbol.symbol_path).await {
+            let symbol_path = symbol.symbol_path;
+            deprecated.extend(
+                cargo::parse_blocking(move || cargo::extract_deprecations(&page, &symbol_path))
+                    .await?,
+            );
+        }
+    }
+
+    Ok(Response { deprecated })
+}
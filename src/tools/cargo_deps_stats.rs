@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// packages of the resolve graph, workspace members included
+    pub total_packages: usize,
+    /// distinct packages the root package (every member of a virtual workspace) depends on
+    pub direct_dependencies: usize,
+    /// direct dependencies per kind, one declared with several kinds counts in each
+    pub direct_by_kind: KindCounts,
+    /// number of edges from the root to the farthest package, taking the shortest path to each
+    pub max_depth: usize,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct KindCounts {
+    pub normal: usize,
+    pub dev: usize,
+    pub build: usize,
+}
+
+/// Size and depth of the resolve graph, for an overview before `cargo_tree`
+pub async fn run() -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let nodes = cargo::resolve_nodes(&metadata);
+    let roots = cargo::graph_roots(&metadata);
+
+    let mut direct: HashMap<&cargo_metadata::PackageId, Vec<String>> = HashMap::new();
+    for root in &roots {
+        for dep in nodes.get(root).into_iter().flat_map(|n| &n.deps) {
+            direct
+                .entry(&dep.pkg)
+                .or_default()
+                .extend(cargo::dependency_kinds(
+                    dep.dep_kinds.iter().map(|k| k.kind),
+                ));
+        }
+    }
+    let mut direct_by_kind = KindCounts::default();
+    for kinds in direct.values_mut() {
+        kinds.sort();
+        kinds.dedup();
+        for kind in kinds.iter() {
+            match kind.as_str() {
+                "dev" => direct_by_kind.dev += 1,
+                "build" => direct_by_kind.build += 1,
+                _ => direct_by_kind.normal += 1,
+            }
+        }
+    }
+
+    let mut seen: HashSet<&cargo_metadata::PackageId> = roots.iter().copied().collect();
+    let mut queue: VecDeque<(&cargo_metadata::PackageId, usize)> =
+        roots.iter().map(|root| (*root, 0)).collect();
+    let mut max_depth = 0;
+    while let Some((id, depth)) = queue.pop_front() {
+        max_depth = max_depth.max(depth);
+        for dep in nodes.get(id).into_iter().flat_map(|n| &n.deps) {
+            if seen.insert(&dep.pkg) {
+                queue.push_back((&dep.pkg, depth + 1));
+            }
+        }
+    }
+
+    Ok(Response {
+        total_packages: nodes.len(),
+        direct_dependencies: direct.len(),
+        direct_by_kind,
+        max_depth,
+    })
+}
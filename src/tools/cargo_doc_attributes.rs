@@ -0,0 +1,37 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// symbol path relative to crate docs, e.g. `struct.Foo` or `de/enum.Error`
+    pub symbol_path: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    pub items: Vec<cargo::ItemAttributes>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let rel = cargo::symbol_rel_path(&req.symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(&target, &rel).await?;
+
+    let item_name = cargo::symbol_name(&rel).unwrap_or_else(|| req.symbol_path.clone());
+
+    let items =
+        cargo::parse_blocking(move || cargo::extract_item_attributes(&html, &item_name)).await?;
+
+    Ok(Response { items })
+}
@@ -0,0 +1,34 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// symbol path relative to crate docs, e.g. `struct.Foo` or `de/struct.Deserializer`
+    pub symbol_path: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// `Examples` sections in page order, empty when the page has none
+    pub examples: Vec<cargo::ExampleSection>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let rel = cargo::symbol_rel_path(&req.symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(&target, &rel).await?;
+
+    let examples = cargo::parse_blocking(move || cargo::extract_examples(&html)).await?;
+    Ok(Response { examples })
+}
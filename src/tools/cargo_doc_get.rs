@@ -2,35 +2,143 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::cargo;
+use crate::error::CopilotError;
+use crate::markdown;
+
+#[derive(Debug, Default, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// the whole rendered page
+    #[default]
+    Full,
+    /// only the item declaration as a Rust code block, the full page when it has none
+    Signature,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// rustdoc HTML converted to markdown
+    #[default]
+    Markdown,
+    /// the rustdoc HTML of `section#main-content` as generated, links untouched
+    Html,
+}
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct Request {
     /// crate id in the form `name@version` or just `name`
     pub crate_id: String,
-    /// symbol path relative to crate docs, e.g. `macro.anyhow` or `de/struct.Deserializer`
+    /// symbol path relative to crate docs, e.g. `macro.anyhow` or `de/struct.Deserializer`;
+    /// links in the returned markdown use the same form
     pub symbol_path: String,
+    /// keep raw HTML tags embedded in doc comments instead of stripping them
+    #[serde(default)]
+    pub raw_html: bool,
+    /// what to return, defaults to `full`
+    #[serde(default)]
+    pub mode: Mode,
+    /// markdown or the original HTML, defaults to `markdown`
+    #[serde(default)]
+    pub format: Format,
+    /// return at most this many bytes, cut at a paragraph boundary; the whole page when omitted
+    pub max_bytes: Option<usize>,
+    /// `next_cursor` of the previous call, to continue a page cut by `max_bytes`
+    #[serde(default)]
+    pub cursor: usize,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
 }
 
-pub async fn run(req: &Request) -> Result<String, String> {
-    let crate_name = req.crate_id.split('@').next().unwrap_or(&req.crate_id);
-    cargo::doc(crate_name).await?;
+/// A page, or the slice of it selected by `cursor` and `max_bytes`
+pub struct Response {
+    /// markdown, or HTML with [`Format::Html`]
+    pub content: String,
+    /// where the next slice starts, `None` on the last one
+    pub next_cursor: Option<usize>,
+}
 
-    let mut rel = req.symbol_path.trim().trim_start_matches('/').to_string();
-    if !rel.ends_with(".html") {
-        rel.push_str(".html");
-    }
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
 
-    let html = cargo::read_doc_html_by_rel_path(crate_name, &rel).await?;
+    let page = match req.format {
+        Format::Markdown => {
+            let options = markdown::Options {
+                raw_html: req.raw_html,
+            };
+            page_markdown(&target, &req.symbol_path, options, req.mode).await?
+        }
+        Format::Html => page_html(&target, &req.symbol_path, req.mode).await?,
+    };
+    let max_bytes = req.max_bytes.unwrap_or(usize::MAX);
+    let (slice, next_cursor) = markdown::slice(&page, req.cursor, max_bytes)
+        .ok_or(CopilotError::InvalidCursor(req.cursor))?;
+    Ok(Response {
+        content: slice.to_string(),
+        next_cursor,
+    })
+}
 
-    let md = tokio::task::spawn_blocking(move || {
+/// Inner HTML of `section#main-content` of one symbol page, or the `pre.item-decl` element in
+/// [`Mode::Signature`], without conversion
+pub async fn page_html(
+    target: &cargo::DocTarget,
+    symbol_path: &str,
+    mode: Mode,
+) -> Result<String, CopilotError> {
+    let rel = cargo::symbol_rel_path(symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(target, &rel).await?;
+
+    cargo::parse_blocking(move || {
         let document = scraper::Html::parse_document(&html);
+        if let Mode::Signature = mode {
+            let selector = scraper::Selector::parse("pre.item-decl").ok()?;
+            if let Some(decl) = document.select(&selector).next() {
+                return Some(decl.html());
+            }
+        }
         let selector = scraper::Selector::parse("section#main-content").ok()?;
-        let content = document.select(&selector).next()?.inner_html();
-        Some(html2md::parse_html(&content))
+        Some(document.select(&selector).next()?.inner_html())
     })
-    .await
-    .map_err(|e| format!("task join error: {}", e))?
-    .ok_or_else(|| "section#main-content not found".to_string())?;
+    .await?
+    .ok_or_else(|| CopilotError::SelectorMissing {
+        selector: "section#main-content",
+        page: rel,
+    })
+}
 
-    Ok(md)
+/// Markdown of one symbol page of already built docs
+pub async fn page_markdown(
+    target: &cargo::DocTarget,
+    symbol_path: &str,
+    options: markdown::Options,
+    mode: Mode,
+) -> Result<String, CopilotError> {
+    let rel = cargo::symbol_rel_path(symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(target, &rel).await?;
+
+    let base_dir = std::path::Path::new(&rel)
+        .parent()
+        .unwrap_or(std::path::Path::new(""))
+        .to_path_buf();
+    cargo::parse_blocking(move || {
+        if let Mode::Signature = mode
+            && let Some(signature) = cargo::extract_signature(&html)
+        {
+            return Some(format!("```rust\n{}\n```\n", signature));
+        }
+        let document = scraper::Html::parse_document(&html);
+        let selector = scraper::Selector::parse("section#main-content").ok()?;
+        let content = document.select(&selector).next()?.inner_html();
+        let content = cargo::rewrite_doc_links(&content, &base_dir);
+        Some(markdown::to_markdown_with(&content, options))
+    })
+    .await?
+    .ok_or_else(|| CopilotError::SelectorMissing {
+        selector: "section#main-content",
+        page: rel,
+    })
 }
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+use crate::markdown;
+use crate::tools::cargo_doc_get;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// symbol paths relative to crate docs, e.g. `struct.Foo` or `de/struct.Deserializer`
+    pub symbol_paths: Vec<String>,
+    /// keep raw HTML tags embedded in doc comments instead of stripping them
+    #[serde(default)]
+    pub raw_html: bool,
+    /// what to return for each symbol, defaults to `full`
+    #[serde(default)]
+    pub mode: cargo_doc_get::Mode,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+/// Either the markdown of a page or why it couldn't be read
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Page {
+    pub markdown: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// resolved `name@version` the docs were built for
+    pub crate_id: String,
+    /// requested symbol path -> page
+    pub pages: BTreeMap<String, Page>,
+}
+
+/// Docs are built once for the whole batch, then pages are read concurrently
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let options = markdown::Options {
+        raw_html: req.raw_html,
+    };
+    let mut pages = BTreeMap::new();
+    let mut pending = req.symbol_paths.iter().cloned();
+    let mut tasks = tokio::task::JoinSet::new();
+    loop {
+        while tasks.len() < cargo::extract_concurrency()
+            && let Some(symbol_path) = pending.next()
+        {
+            let target = target.clone();
+            let mode = req.mode;
+            tasks.spawn(async move {
+                let page = cargo_doc_get::page_markdown(&target, &symbol_path, options, mode).await;
+                (symbol_path, page)
+            });
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let (symbol_path, page) = joined?;
+        let page = match page {
+            Ok(markdown) => Page {
+                markdown: Some(markdown),
+                error: None,
+            },
+            Err(e) => Page {
+                markdown: None,
+                error: Some(e.to_string()),
+            },
+        };
+        pages.insert(symbol_path, page);
+    }
+
+    Ok(Response {
+        crate_id: target.crate_id,
+        pages,
+    })
+}
@@ -0,0 +1,139 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+const DEFAULT_MAX_RESULTS: usize = 20;
+/// Characters of context kept on each side of a match
+const SNIPPET_CONTEXT: usize = 80;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// text searched for in the prose and code of the docs, case insensitive
+    pub query: String,
+    /// maximum number of results, defaults to 20
+    pub max_results: Option<usize>,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GrepMatch {
+    /// page of the match relative to the crate docs, with the `#anchor` of the documented
+    /// member when the match is in a method's, field's or variant's docs
+    pub symbol_path: String,
+    /// text around the first match in the docblock
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// resolved `name@version` the docs were built for
+    pub crate_id: String,
+    /// one match per docblock, ordered by page
+    pub results: Vec<GrepMatch>,
+    /// more docblocks matched than `max_results`
+    pub truncated: bool,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+    let index = cargo::read_doc_index_html(&target).await?;
+    let symbols = cargo::extract_symbols(&index, &target).await?;
+
+    // every page of the crate: its index plus one per item, other crates' pages excluded
+    let mut pages: Vec<String> = symbols
+        .into_iter()
+        .map(|symbol| symbol.symbol_path)
+        .filter(|path| !path.starts_with("../"))
+        .collect();
+    pages.push("index.html".to_string());
+    pages.sort();
+    pages.dedup();
+
+    let query = req.query.trim();
+    let pattern = regex::RegexBuilder::new(&regex::escape(query))
+        .case_insensitive(true)
+        .build()
+        // an escaped query always parses, but a long one can exceed the compiled size limit
+        .map_err(|e| CopilotError::InvalidGrepQuery(e.to_string()))?;
+    let mut results = Vec::new();
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut pages = pages.into_iter().filter(|_| !query.is_empty());
+    loop {
+        while tasks.len() < cargo::extract_concurrency()
+            && let Some(page) = pages.next()
+        {
+            let target = target.clone();
+            let pattern = pattern.clone();
+            tasks.spawn(async move {
+                // ignore missing pages, e.g. items only re-exported from another crate
+                let Ok(html) = cargo::read_doc_html_by_rel_path(&target, &page).await else {
+                    return Ok(Vec::new());
+                };
+                cargo::parse_blocking(move || grep_page(&html, &page, &pattern)).await
+            });
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        results.extend(joined??);
+    }
+
+    // pages complete in arbitrary order; matches within a page keep their order
+    results.sort_by(|a, b| page_of(&a.symbol_path).cmp(page_of(&b.symbol_path)));
+    let max_results = req.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    let truncated = results.len() > max_results;
+    results.truncate(max_results);
+
+    Ok(Response {
+        crate_id: target.crate_id,
+        results,
+        truncated,
+    })
+}
+
+fn page_of(symbol_path: &str) -> &str {
+    symbol_path.split('#').next().unwrap_or_default()
+}
+
+fn grep_page(html: &str, page: &str, pattern: &regex::Regex) -> Vec<GrepMatch> {
+    cargo::extract_docblock_texts(html)
+        .into_iter()
+        .filter_map(|(member, text)| {
+            let found = pattern.find(&text)?;
+            Some(GrepMatch {
+                symbol_path: match member {
+                    Some(anchor) => format!("{}#{}", page, anchor),
+                    None => page.to_string(),
+                },
+                snippet: snippet(&text, found.start(), found.end()),
+            })
+        })
+        .collect()
+}
+
+/// `text[start..end]` with up to [`SNIPPET_CONTEXT`] characters on each side, `…` marking cuts
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let to = text[end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(text.len(), |(i, _)| end + i);
+    format!(
+        "{}{}{}",
+        if from > 0 { "…" } else { "" },
+        &text[from..to],
+        if to < text.len() { "…" } else { "" }
+    )
+}
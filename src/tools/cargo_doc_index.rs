@@ -2,22 +2,143 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Default, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Sort {
+    /// by `symbol_id`, then `symbol_path`
+    #[default]
+    Alphabetical,
+    /// longest `symbol_description` first, undocumented symbols last
+    DocumentedFirst,
+    /// grouped by `symbol_type`, alphabetical within a group
+    Type,
+}
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct Request {
     /// crate id in the form `name@version` or just `name`
     pub crate_id: String,
+    /// ordering of the returned symbols, defaults to `alphabetical`
+    #[serde(default)]
+    pub sort: Sort,
+    /// only return symbols of these types (e.g. `["struct", "enum"]`), all types when empty
+    #[serde(default)]
+    pub symbol_types: Vec<String>,
+    /// only parse these rustdoc sections (e.g. `["traits"]`), skipping the work for the others
+    /// on large crates; all sections when empty
+    #[serde(default)]
+    pub sections: Vec<String>,
+    /// only return symbols whose `symbol_id` contains this, case-insensitive
+    pub query: Option<String>,
+    /// number of symbols to skip, defaults to 0
+    pub offset: Option<usize>,
+    /// maximum number of symbols to return, defaults to all of them
+    pub limit: Option<usize>,
+    /// leave `symbol_description` out and keep only the one-sentence `summary`, for a smaller
+    /// index
+    #[serde(default)]
+    pub summary_only: bool,
+    /// how many module levels below the crate root to read, 0 lists the root page only; modules
+    /// past the limit are still returned as `module` symbols to drill into. Unlimited by default
+    pub max_depth: Option<usize>,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct Response {
+    /// resolved `name@version` the docs were built for
+    pub crate_id: String,
+    /// `crates-io`, `git <url>`, `registry <url>` or `path <dir>`
+    pub crate_source: String,
+    /// number of symbols before pagination
+    pub total: usize,
+    /// `offset` of the next page, absent on the last page
+    pub next_offset: Option<usize>,
     pub symbols: Vec<cargo::SymbolInfo>,
 }
 
-pub async fn run(req: &Request) -> Result<Response, String> {
-    let crate_name = req.crate_id.split('@').next().unwrap_or(&req.crate_id);
-    cargo::doc(crate_name).await?;
-    let html = cargo::read_doc_index_html(crate_name).await?;
-    let symbols = cargo::extract_symbols(&html, crate_name).await?;
-    Ok(Response { symbols })
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    if let Some(unknown) = req
+        .symbol_types
+        .iter()
+        .find(|t| !cargo::symbol_types().any(|known| known == t.as_str()))
+    {
+        return Err(CopilotError::UnknownSymbolType {
+            symbol_type: unknown.clone(),
+            valid: cargo::symbol_types().collect(),
+        });
+    }
+
+    if let Some(unknown) = req
+        .sections
+        .iter()
+        .find(|s| !cargo::section_ids().any(|known| known == s.as_str()))
+    {
+        return Err(CopilotError::UnknownSection {
+            section: unknown.clone(),
+            valid: cargo::section_ids().collect(),
+        });
+    }
+
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+    let html = cargo::read_doc_index_html(&target).await?;
+    let scope = cargo::SymbolScope {
+        max_depth: req.max_depth,
+        sections: req.sections.clone(),
+    };
+    let mut symbols = cargo::extract_symbols_in(&html, &target, &scope).await?;
+    if !req.symbol_types.is_empty() {
+        symbols.retain(|s| req.symbol_types.contains(&s.symbol_type));
+    }
+    if let Some(query) = req.query.as_deref().filter(|q| !q.is_empty()) {
+        let query = query.to_lowercase();
+        symbols.retain(|s| s.symbol_id.to_lowercase().contains(&query));
+    }
+    sort_symbols(&mut symbols, req.sort);
+
+    let total = symbols.len();
+    let offset = req.offset.unwrap_or(0).min(total);
+    let end = req
+        .limit
+        .map_or(total, |limit| offset.saturating_add(limit).min(total));
+    let mut symbols: Vec<cargo::SymbolInfo> = symbols.drain(offset..end).collect();
+    if req.summary_only {
+        for symbol in &mut symbols {
+            symbol.symbol_description = None;
+        }
+    }
+
+    Ok(Response {
+        crate_id: target.crate_id,
+        crate_source: target.source,
+        total,
+        next_offset: (end < total).then_some(end),
+        symbols,
+    })
+}
+
+fn sort_symbols(symbols: &mut [cargo::SymbolInfo], sort: Sort) {
+    let alphabetical = |a: &cargo::SymbolInfo, b: &cargo::SymbolInfo| {
+        a.symbol_id
+            .cmp(&b.symbol_id)
+            .then_with(|| a.symbol_path.cmp(&b.symbol_path))
+    };
+    match sort {
+        Sort::Alphabetical => symbols.sort_by(alphabetical),
+        Sort::DocumentedFirst => symbols.sort_by(|a, b| {
+            let len = |s: &cargo::SymbolInfo| s.symbol_description.as_ref().map_or(0, |d| d.len());
+            len(b).cmp(&len(a)).then_with(|| alphabetical(a, b))
+        }),
+        Sort::Type => symbols.sort_by(|a, b| {
+            a.symbol_type
+                .cmp(&b.symbol_type)
+                .then_with(|| alphabetical(a, b))
+        }),
+    }
 }
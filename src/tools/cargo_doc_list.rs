@@ -0,0 +1,36 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DocumentedCrate {
+    /// `name@version` of the package the docs were generated for, absent when no package of the
+    /// dependency graph matches (e.g. docs of a version upgraded since)
+    pub crate_id: Option<String>,
+    /// directory under `target/doc`
+    pub doc_name: String,
+    /// version shown on the generated docs
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// crates whose docs are already built, `cargo_doc_*` calls on them skip most of the build
+    pub crates: Vec<DocumentedCrate>,
+}
+
+pub async fn run() -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let crates = cargo::scan_doc_dirs(&metadata)
+        .await?
+        .into_iter()
+        .map(|dir| DocumentedCrate {
+            crate_id: dir.target.map(|target| target.crate_id),
+            doc_name: dir.doc_name,
+            version: dir.version,
+        })
+        .collect();
+    Ok(Response { crates })
+}
@@ -2,19 +2,53 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::cargo;
+use crate::error::CopilotError;
+use crate::markdown;
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct Request {
     /// crate id in the form `name@version` or just `name`
     pub crate_id: String,
+    /// keep raw HTML tags embedded in doc comments instead of stripping them
+    #[serde(default)]
+    pub raw_html: bool,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
 }
 
-pub async fn run(req: &Request) -> Result<String, String> {
-    let crate_name = req.crate_id.split('@').next().unwrap_or(&req.crate_id);
-    cargo::doc(crate_name).await?;
-    let html = cargo::read_doc_index_html(crate_name).await?;
-    let docblock_html = cargo::extract_docblock(&html)
-        .ok_or_else(|| "no <div \"docblock\"> found in index.html".to_string())?;
+pub async fn run(req: &Request) -> Result<String, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+    let docblock_html = crate_docblock(&target).await?;
+    let options = markdown::Options {
+        raw_html: req.raw_html,
+    };
+    let md =
+        cargo::parse_blocking(move || markdown::to_markdown_with(&docblock_html, options)).await?;
 
-    Ok(html2md::parse_html(&docblock_html))
+    // a version alone doesn't identify git/path packages, so say where the docs came from
+    if target.is_crates_io() {
+        Ok(md)
+    } else {
+        Ok(format!(
+            "> Documented from {} ({})\n\n{}",
+            target.source, target.crate_id, md
+        ))
+    }
+}
+
+/// HTML of the crate level docs on index.html, links rewritten to symbol paths
+pub async fn crate_docblock(target: &cargo::DocTarget) -> Result<String, CopilotError> {
+    let html = cargo::read_doc_index_html(target).await?;
+    cargo::parse_blocking(move || {
+        cargo::extract_docblock(&html)
+            .map(|docblock_html| cargo::rewrite_doc_links(&docblock_html, std::path::Path::new("")))
+    })
+    .await?
+    .ok_or_else(|| CopilotError::SelectorMissing {
+        selector: "div.docblock",
+        page: "index.html".to_string(),
+    })
 }
@@ -0,0 +1,115 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+use crate::markdown;
+use crate::tools::cargo_doc_overview;
+
+/// `heading` of the content before the first heading
+const INTRO: &str = "intro";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// keep raw HTML tags embedded in doc comments instead of stripping them
+    #[serde(default)]
+    pub raw_html: bool,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// resolved `name@version` the docs were built for
+    pub crate_id: String,
+    /// `crates-io`, `git <url>`, `registry <url>` or `path <dir>`
+    pub crate_source: String,
+    /// the crate level docs split at their top level headings, in page order
+    pub sections: Vec<Section>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Section {
+    /// heading text, `intro` for the content before the first heading
+    pub heading: String,
+    /// section body without the heading
+    pub markdown: String,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+    let docblock_html = cargo_doc_overview::crate_docblock(&target).await?;
+    let options = markdown::Options {
+        raw_html: req.raw_html,
+    };
+    let sections = cargo::parse_blocking(move || {
+        split_sections(&docblock_html)
+            .into_iter()
+            .map(|(heading, html)| Section {
+                heading,
+                markdown: markdown::to_markdown_with(&html, options)
+                    .trim()
+                    .to_string(),
+            })
+            .filter(|section| section.heading != INTRO || !section.markdown.is_empty())
+            .collect()
+    })
+    .await?;
+
+    Ok(Response {
+        crate_id: target.crate_id,
+        crate_source: target.source,
+        sections,
+    })
+}
+
+/// `(heading, body html)` at each heading of the highest level used, `# Title` in a doc comment
+/// renders as `h2`, `## Title` as `h3`
+fn split_sections(docblock_html: &str) -> Vec<(String, String)> {
+    let fragment = scraper::Html::parse_fragment(docblock_html);
+    let root = fragment.root_element();
+    let top_level = root
+        .child_elements()
+        .filter_map(|el| heading_level(&el))
+        .min();
+
+    let mut sections = vec![(INTRO.to_string(), String::new())];
+    for node in root.children() {
+        if let Some(el) = scraper::ElementRef::wrap(node)
+            && heading_level(&el).is_some()
+            && heading_level(&el) == top_level
+        {
+            // rustdoc prefixes headings with a `§` anchor link
+            let text: String = el.text().collect();
+            let heading = text.trim().trim_start_matches('§').trim().to_string();
+            sections.push((heading, String::new()));
+            continue;
+        }
+        let body = &mut sections.last_mut().expect("starts with the intro").1;
+        match scraper::ElementRef::wrap(node) {
+            Some(el) => body.push_str(&el.html()),
+            None => {
+                if let Some(text) = node.value().as_text() {
+                    body.push_str(&escape_text(text));
+                }
+            }
+        }
+    }
+    sections
+}
+
+fn heading_level(el: &scraper::ElementRef) -> Option<u8> {
+    let level = el.value().name().strip_prefix('h')?.parse::<u8>().ok()?;
+    (1..=6).contains(&level).then_some(level)
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
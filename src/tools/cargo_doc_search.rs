@@ -0,0 +1,119 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+const DEFAULT_MAX_RESULTS: usize = 20;
+/// Score of a match found only in the description, below any name match
+const DESCRIPTION_SCORE: u32 = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// fuzzy query matched against symbol names, case and `_` insensitive; empty lists the
+    /// crate's top-level items
+    pub query: String,
+    /// also match the query against symbol descriptions
+    #[serde(default)]
+    pub include_description: bool,
+    /// maximum number of results, defaults to 20
+    pub max_results: Option<usize>,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub symbol: cargo::SymbolInfo,
+    /// match quality, higher is better
+    pub score: u32,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// resolved `name@version` the docs were built for
+    pub crate_id: String,
+    /// best matches first
+    pub results: Vec<SearchResult>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+    let html = cargo::read_doc_index_html(&target).await?;
+    let symbols = cargo::extract_symbols(&html, &target).await?;
+
+    let query = normalize(&req.query);
+    let mut results: Vec<SearchResult> = symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            let score = if query.is_empty() {
+                // top-level items live directly in the crate docs dir
+                (!symbol.symbol_path.contains('/')).then_some(0)?
+            } else {
+                let name_score = score(&query, &symbol.symbol_id);
+                let desc_score = symbol
+                    .symbol_description
+                    .as_deref()
+                    .filter(|desc| req.include_description && normalize(desc).contains(&query))
+                    .map(|_| DESCRIPTION_SCORE);
+                name_score.into_iter().chain(desc_score).max()?
+            };
+            Some(SearchResult { symbol, score })
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.symbol.symbol_id.len().cmp(&b.symbol.symbol_id.len()))
+            .then_with(|| a.symbol.symbol_path.cmp(&b.symbol.symbol_path))
+    });
+    results.truncate(req.max_results.unwrap_or(DEFAULT_MAX_RESULTS));
+
+    Ok(Response {
+        crate_id: target.crate_id,
+        results,
+    })
+}
+
+/// Lowercase and drop `_`/`-` so `snake_case`, `CamelCase` and `kebab-case` spellings compare equal
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Rank `name` against an already normalized query: exact > prefix > substring > subsequence >
+/// similar spelling. `None` when it doesn't match at all.
+fn score(query: &str, name: &str) -> Option<u32> {
+    let name = normalize(name);
+    // penalize extra characters so tighter matches win within a tier
+    let extra = (name.len().saturating_sub(query.len()) as u32).min(99);
+
+    if name == query {
+        return Some(1000);
+    }
+    if name.starts_with(query) {
+        return Some(800 - extra);
+    }
+    if name.contains(query) {
+        return Some(600 - extra);
+    }
+    if is_subsequence(query, &name) {
+        return Some(400 - extra);
+    }
+    let similarity = strsim::jaro_winkler(query, &name);
+    (similarity >= 0.85).then(|| 100 + (similarity * 100.0) as u32)
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
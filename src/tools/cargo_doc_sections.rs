@@ -0,0 +1,42 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// symbol path relative to crate docs, e.g. `fn.parse` or `struct.Foo`; add a member
+    /// anchor such as `struct.Foo#method.new` to read that method's sections
+    pub symbol_path: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// `Panics`, `Errors` and/or `Safety` section name -> markdown, only those present
+    pub sections: std::collections::BTreeMap<String, String>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let rel = cargo::symbol_rel_path(&req.symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(&target, &rel).await?;
+
+    let member = req
+        .symbol_path
+        .split_once('#')
+        .map(|(_, anchor)| anchor.to_string())
+        .filter(|anchor| !anchor.is_empty());
+    let sections =
+        cargo::parse_blocking(move || cargo::extract_doc_sections(&html, member.as_deref()))
+            .await?;
+    Ok(Response { sections })
+}
@@ -0,0 +1,102 @@
+use std::collections::{BTreeMap, HashMap};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// crates resolved at more than one version, sorted by name
+    pub duplicates: Vec<Duplicate>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Duplicate {
+    pub name: String,
+    /// oldest first
+    pub versions: Vec<DuplicateVersion>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DuplicateVersion {
+    pub version: String,
+    /// packages depending on this version, sorted by crate id
+    pub dependents: Vec<Dependent>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Dependent {
+    /// `name@version` of the dependent
+    pub crate_id: String,
+    /// every kind the dependency is declared with: normal|dev|build
+    pub kinds: Vec<String>,
+}
+
+/// Crates of the resolve graph present at several versions, with the packages pulling in each
+pub async fn run() -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let nodes = cargo::resolve_nodes(&metadata);
+
+    let mut by_name: BTreeMap<&str, Vec<&cargo_metadata::Package>> = BTreeMap::new();
+    for pkg in metadata
+        .packages
+        .iter()
+        .filter(|pkg| nodes.contains_key(&pkg.id))
+    {
+        by_name.entry(pkg.name.as_str()).or_default().push(pkg);
+    }
+    by_name.retain(|_, versions| versions.len() > 1);
+    if by_name.is_empty() {
+        return Ok(Response {
+            duplicates: Vec::new(),
+        });
+    }
+
+    let packages: HashMap<&cargo_metadata::PackageId, &cargo_metadata::Package> =
+        metadata.packages.iter().map(|pkg| (&pkg.id, pkg)).collect();
+    let mut dependents: HashMap<&cargo_metadata::PackageId, BTreeMap<String, Vec<String>>> =
+        HashMap::new();
+    for node in nodes.values() {
+        let Some(pkg) = packages.get(&node.id) else {
+            continue;
+        };
+        for dep in &node.deps {
+            // a package depending on another under several target tables yields several node deps
+            let kinds = dependents
+                .entry(&dep.pkg)
+                .or_default()
+                .entry(format!("{}@{}", pkg.name, pkg.version))
+                .or_default();
+            kinds.extend(cargo::dependency_kinds(
+                dep.dep_kinds.iter().map(|k| k.kind),
+            ));
+            kinds.sort();
+            kinds.dedup();
+        }
+    }
+
+    let duplicates = by_name
+        .into_iter()
+        .map(|(name, mut versions)| {
+            versions.sort_by(|a, b| a.version.cmp(&b.version));
+            Duplicate {
+                name: name.to_string(),
+                versions: versions
+                    .into_iter()
+                    .map(|pkg| DuplicateVersion {
+                        version: pkg.version.to_string(),
+                        dependents: dependents
+                            .remove(&pkg.id)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(crate_id, kinds)| Dependent { crate_id, kinds })
+                            .collect(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    Ok(Response { duplicates })
+}
@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// symbol path relative to crate docs, e.g. `enum.Error` or `de/enum.Unexpected`
+    pub symbol_path: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// variants in declaration order
+    pub variants: Vec<cargo::VariantInfo>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let rel = cargo::symbol_rel_path(&req.symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(&target, &rel).await?;
+
+    let variants = cargo::parse_blocking(move || cargo::extract_enum_variants(&html)).await?;
+
+    Ok(Response { variants })
+}
@@ -0,0 +1,62 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// module or item to expand, e.g. `parser::Token`; the whole crate otherwise
+    pub item: Option<String>,
+    /// workspace member (`name` or `name@version`) to expand, defaults to the root package
+    pub package: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version` of the expanded package
+    pub crate_id: String,
+    /// source after macro expansion, as printed by `cargo expand`
+    pub source: String,
+}
+
+/// Shells out to the `cargo expand` subcommand, which has to be installed separately
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_workspace_member(&metadata, req.package.as_deref())?;
+
+    let mut cmd = cargo::cargo_command();
+    cmd.arg("expand")
+        .arg("--color=never")
+        .arg("--package")
+        .arg(cargo::package_spec(pkg));
+    if cargo::offline() {
+        cmd.arg("--offline");
+    }
+    if let Some(item) = req.item.as_deref().map(str::trim).filter(|i| !i.is_empty()) {
+        // `--` keeps an item starting with a dash from being read as a flag
+        cmd.arg("--").arg(item);
+    }
+    let output = cargo::output_in_group(&mut cmd)
+        .await
+        .map_err(|source| CopilotError::Spawn {
+            command: "cargo",
+            source,
+        })?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        if stderr.contains("no such command: `expand`") {
+            return Err(CopilotError::CargoExpandNotInstalled);
+        }
+        return Err(CopilotError::CargoExpandFailed {
+            status: output.status,
+            stderr: stderr.trim().to_string(),
+        });
+    }
+
+    Ok(Response {
+        crate_id: format!("{}@{}", pkg.name, pkg.version),
+        source: String::from_utf8_lossy(&output.stdout).into_owned(),
+    })
+}
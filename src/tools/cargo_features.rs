@@ -0,0 +1,108 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FeatureInfo {
+    pub name: String,
+    /// other features of the same package this feature enables
+    pub implies: Vec<String>,
+    /// optional dependencies this feature activates
+    pub dependencies: Vec<String>,
+    /// features enabled on dependencies, as `dep/feature` (`dep?/feature` doesn't activate `dep`)
+    pub dependency_features: Vec<String>,
+    /// whether `default` enables this feature, directly or transitively
+    pub default: bool,
+    /// whether the feature is enabled when building the workspace with its default features
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version`
+    pub crate_id: String,
+    pub features: Vec<FeatureInfo>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_package(&metadata, &req.crate_id)?;
+
+    let optional_deps: Vec<&str> = pkg
+        .dependencies
+        .iter()
+        .filter(|d| d.optional)
+        .map(|d| d.rename.as_deref().unwrap_or(&d.name))
+        .collect();
+    let enabled: &[String] =
+        cargo::find_root_resolve_node(&metadata, pkg).map_or(&[], |node| &node.features);
+    let default = default_features(pkg);
+
+    let features = pkg
+        .features
+        .iter()
+        .map(|(name, entries)| {
+            let mut info = FeatureInfo {
+                name: name.clone(),
+                implies: Vec::new(),
+                dependencies: Vec::new(),
+                dependency_features: Vec::new(),
+                default: default.contains(name),
+                enabled: enabled.contains(name),
+            };
+            for entry in entries {
+                if let Some(dep) = entry.strip_prefix("dep:") {
+                    info.dependencies.push(dep.to_string());
+                } else if let Some((dep, _)) = entry.split_once('/') {
+                    info.dependency_features.push(entry.clone());
+                    // `dep/feature` also activates an optional `dep`, `dep?/feature` doesn't
+                    if optional_deps.contains(&dep) {
+                        info.dependencies.push(dep.to_string());
+                    }
+                } else if pkg.features.contains_key(entry) {
+                    info.implies.push(entry.clone());
+                } else if optional_deps.contains(&entry.as_str()) {
+                    // pre-`dep:` manifests activate optional dependencies by name
+                    info.dependencies.push(entry.clone());
+                }
+            }
+            info.dependencies.sort();
+            info.dependencies.dedup();
+            info
+        })
+        .collect();
+
+    Ok(Response {
+        crate_id: format!("{}@{}", pkg.name, pkg.version),
+        features,
+    })
+}
+
+/// `default` and every feature it enables transitively
+fn default_features(pkg: &cargo_metadata::Package) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut pending = vec!["default".to_string()];
+    while let Some(feature) = pending.pop() {
+        if seen.contains(&feature) {
+            continue;
+        }
+        if let Some(entries) = pkg.features.get(&feature) {
+            pending.extend(
+                entries
+                    .iter()
+                    .filter(|e| pkg.features.contains_key(*e))
+                    .cloned(),
+            );
+            seen.push(feature);
+        }
+    }
+    seen
+}
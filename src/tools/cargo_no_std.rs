@@ -0,0 +1,115 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version`
+    pub crate_id: String,
+    /// `yes`, `no`, `with-feature:<name>` or `unknown`
+    pub no_std: String,
+    /// which signal the verdict is based on
+    pub reason: String,
+}
+
+/// Heuristic: feature names first, then crates.io tags, then the lib root's `no_std` attribute
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_package(&metadata, &req.crate_id)?;
+
+    let (no_std, reason) = detect(pkg).await;
+    Ok(Response {
+        crate_id: format!("{}@{}", pkg.name, pkg.version),
+        no_std,
+        reason,
+    })
+}
+
+async fn detect(pkg: &cargo_metadata::Package) -> (String, String) {
+    if let Some(name) = ["no_std", "no-std"]
+        .into_iter()
+        .find(|f| pkg.features.contains_key(*f))
+    {
+        return (
+            format!("with-feature:{}", name),
+            format!("declares a `{}` feature", name),
+        );
+    }
+
+    if pkg.features.contains_key("std") {
+        let reason = if enables(pkg, "default", "std", &mut Vec::new()) {
+            "has a default-on `std` feature; use `default-features = false` for no_std"
+        } else {
+            "has an opt-in `std` feature"
+        };
+        let reason = if pkg.features.contains_key("alloc") {
+            format!("{}, `alloc` is available separately", reason)
+        } else {
+            reason.to_string()
+        };
+        return ("yes".to_string(), reason);
+    }
+
+    let tagged = pkg.categories.iter().any(|c| c.starts_with("no-std"))
+        || pkg.keywords.iter().any(|k| k == "no_std" || k == "no-std");
+    if tagged {
+        return (
+            "yes".to_string(),
+            "tagged `no-std` in categories/keywords".to_string(),
+        );
+    }
+
+    let Some(lib) = pkg
+        .targets
+        .iter()
+        .find(|t| t.kind.iter().any(|k| k.contains("lib")))
+    else {
+        return (
+            "unknown".to_string(),
+            "package has no lib target".to_string(),
+        );
+    };
+    match tokio::fs::read_to_string(&lib.src_path).await {
+        Ok(src) if src.contains("#![no_std]") => (
+            "yes".to_string(),
+            "lib root declares `#![no_std]`".to_string(),
+        ),
+        Ok(src) if src.contains("no_std)]") => (
+            "unknown".to_string(),
+            "lib root is conditionally `no_std` via `cfg_attr`".to_string(),
+        ),
+        Ok(_) => (
+            "no".to_string(),
+            "no `std` feature, no-std tag or `no_std` attribute".to_string(),
+        ),
+        Err(e) => (
+            "unknown".to_string(),
+            format!("failed to read {}: {}", lib.src_path, e),
+        ),
+    }
+}
+
+/// Whether `feature` (transitively) enables `target`
+fn enables(
+    pkg: &cargo_metadata::Package,
+    feature: &str,
+    target: &str,
+    seen: &mut Vec<String>,
+) -> bool {
+    if seen.iter().any(|f| f == feature) {
+        return false;
+    }
+    seen.push(feature.to_string());
+    pkg.features.get(feature).is_some_and(|deps| {
+        deps.iter()
+            .any(|d| d == target || enables(pkg, d, target, seen))
+    })
+}
@@ -0,0 +1,136 @@
+use cargo_metadata::semver::Version;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+use crate::registry;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// workspace member (`name` or `name@version`) whose direct dependencies are checked,
+    /// defaults to the root package
+    pub package: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version` of the checked package
+    pub crate_id: String,
+    pub dependencies: Vec<OutdatedInfo>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct OutdatedInfo {
+    /// package name
+    pub name: String,
+    /// name the dependency is renamed to in the manifest
+    pub rename: Option<String>,
+    /// version requirement from the manifest, e.g. `^1.2`
+    pub req: String,
+    /// how the package depends on it, any of normal|dev|build
+    pub kinds: Vec<String>,
+    /// locked version, `null` when the dependency isn't in the resolved graph (an optional
+    /// dependency no feature enables)
+    pub current: Option<String>,
+    /// highest non-yanked release `req` accepts
+    pub latest_compatible: Option<String>,
+    /// highest non-yanked release, preferring stable ones
+    pub latest: Option<String>,
+    /// why the dependency wasn't checked (git, path or other registry source, unpublished)
+    pub note: Option<String>,
+}
+
+/// Compares every direct dependency declared in the manifest against its crates.io releases;
+/// one registry request per crates.io dependency
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_workspace_member(&metadata, req.package.as_deref())?;
+
+    let mut declared: Vec<&cargo_metadata::Dependency> = pkg.dependencies.iter().collect();
+    declared.sort_by(|a, b| (&a.name, a.req.to_string()).cmp(&(&b.name, b.req.to_string())));
+
+    let mut dependencies: Vec<OutdatedInfo> = Vec::new();
+    for dep in declared {
+        // the same requirement declared as normal and dev dependency is checked once
+        if let Some(last) = dependencies
+            .last_mut()
+            .filter(|last| last.name == dep.name && last.req == dep.req.to_string())
+        {
+            last.kinds.extend(cargo::dependency_kinds([dep.kind]));
+            last.kinds.sort();
+            last.kinds.dedup();
+            continue;
+        }
+        dependencies.push(check(&metadata, dep).await?);
+    }
+
+    Ok(Response {
+        crate_id: format!("{}@{}", pkg.name, pkg.version),
+        dependencies,
+    })
+}
+
+async fn check(
+    metadata: &cargo_metadata::Metadata,
+    dep: &cargo_metadata::Dependency,
+) -> Result<OutdatedInfo, CopilotError> {
+    let mut info = OutdatedInfo {
+        name: dep.name.clone(),
+        rename: dep.rename.clone(),
+        req: dep.req.to_string(),
+        kinds: cargo::dependency_kinds([dep.kind]),
+        current: None,
+        latest_compatible: None,
+        latest: None,
+        note: None,
+    };
+
+    let locked = metadata
+        .packages
+        .iter()
+        .find(|p| p.name == dep.name && dep.req.matches(&p.version));
+    info.current = locked.map(|p| p.version.to_string());
+
+    // `source` is `None` for path dependencies, for others the locked package's says more
+    // than the declaration (a `[patch]` can redirect crates.io to git)
+    let source = locked
+        .map(|p| p.source.as_ref().map(|s| s.repr.clone()))
+        .unwrap_or_else(|| dep.source.clone());
+    match source.as_deref() {
+        None => {
+            info.note = Some("path dependency, not checked".to_string());
+            return Ok(info);
+        }
+        Some(s) if s.starts_with("git+") => {
+            info.note = Some(format!("git dependency ({}), not checked", s));
+            return Ok(info);
+        }
+        Some(s) if !cargo::CRATES_IO_SOURCES.contains(&s) => {
+            info.note = Some(format!("not from crates.io ({}), not checked", s));
+            return Ok(info);
+        }
+        Some(_) => {}
+    }
+
+    let versions = match registry::versions(&dep.name).await {
+        Ok(versions) => versions,
+        Err(CopilotError::CrateNotPublished(_)) => {
+            info.note = Some("not found on crates.io".to_string());
+            return Ok(info);
+        }
+        Err(e) => return Err(e),
+    };
+
+    // highest first, so the first match is the highest compatible release
+    info.latest_compatible = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .find(|v| Version::parse(&v.version).is_ok_and(|num| dep.req.matches(&num)))
+        .map(|v| v.version.clone());
+    info.latest = versions
+        .iter()
+        .find(|v| v.is_latest)
+        .map(|v| v.version.clone());
+    Ok(info)
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DependencyInfo {
+    /// dependency package name
+    pub name: String,
+    /// name the dependency is renamed to, if any
+    pub rename: Option<String>,
+    /// version requirement, e.g. `^1.0`
+    pub req: String,
+    /// normal|dev|build
+    pub kind: String,
+    pub optional: bool,
+    /// features explicitly enabled on the dependency
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version`
+    pub crate_id: String,
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub license: Option<String>,
+    pub repository: Option<String>,
+    pub homepage: Option<String>,
+    pub documentation: Option<String>,
+    /// Rust edition, e.g. `2021`
+    pub edition: String,
+    /// minimum supported Rust version (`package.rust-version`)
+    pub rust_version: Option<String>,
+    /// feature name -> features and optional dependencies it enables
+    pub features: BTreeMap<String, Vec<String>>,
+    /// direct dependencies as declared in the manifest
+    pub dependencies: Vec<DependencyInfo>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_package(&metadata, &req.crate_id)?;
+
+    let dependencies = pkg
+        .dependencies
+        .iter()
+        .map(|d| DependencyInfo {
+            name: d.name.clone(),
+            rename: d.rename.clone(),
+            req: d.req.to_string(),
+            kind: d.kind.to_string(),
+            optional: d.optional,
+            features: d.features.clone(),
+        })
+        .collect();
+
+    Ok(Response {
+        crate_id: format!("{}@{}", pkg.name, pkg.version),
+        name: pkg.name.clone(),
+        version: pkg.version.to_string(),
+        description: pkg.description.clone(),
+        authors: pkg.authors.clone(),
+        license: pkg.license.clone(),
+        repository: pkg.repository.clone(),
+        homepage: pkg.homepage.clone(),
+        documentation: pkg.documentation.clone(),
+        edition: pkg.edition.to_string(),
+        rust_version: pkg.rust_version.as_ref().map(|v| v.to_string()),
+        features: pkg.features.clone(),
+        dependencies,
+    })
+}
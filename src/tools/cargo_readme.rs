@@ -0,0 +1,60 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// id formatted as `name@version`
+    pub crate_id: String,
+    /// absolute path of the README file
+    pub path: String,
+    /// `markdown` for Markdown and HTML READMEs (the latter converted), otherwise the file
+    /// extension (e.g. `rst`, `adoc`) or `text`, returned verbatim
+    pub format: String,
+    pub content: String,
+}
+
+/// `package.readme` is relative to the manifest directory; cargo fills it in with an existing
+/// `README.md`, `README.txt` or `README` when the manifest doesn't set it
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_package(&metadata, &req.crate_id)?;
+    let crate_id = format!("{}@{}", pkg.name, pkg.version);
+
+    let (Some(readme), Some(manifest_dir)) = (&pkg.readme, pkg.manifest_path.parent()) else {
+        return Err(CopilotError::NoReadme(crate_id));
+    };
+    let path = manifest_dir.join(readme).into_std_path_buf();
+    let raw = match tokio::fs::read_to_string(&path).await {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(CopilotError::ReadmeMissing { crate_id, path });
+        }
+        Err(e) => return Err(CopilotError::io("read", path, e)),
+    };
+
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    let (format, content) = match extension.as_deref() {
+        Some("md" | "markdown") => ("markdown".to_string(), raw),
+        Some("html" | "htm") => ("markdown".to_string(), crate::markdown::to_markdown(&raw)),
+        Some(ext) if !ext.is_empty() && ext != "txt" => (ext.to_string(), raw),
+        _ => ("text".to_string(), raw),
+    };
+
+    Ok(Response {
+        crate_id,
+        path: path.display().to_string(),
+        format,
+        content,
+    })
+}
@@ -0,0 +1,70 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// also list re-exports of nested modules, not just the crate root
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    pub reexports: Vec<cargo::ReexportInfo>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let mut reexports = Vec::new();
+    // queue of (module page relative to the crate docs, module path)
+    let mut queue =
+        std::collections::VecDeque::from([("index.html".to_string(), target.doc_name.clone())]);
+    let mut visited = std::collections::HashSet::new();
+
+    while let Some((page, module_path)) = queue.pop_front() {
+        if !visited.insert(page.clone()) {
+            continue;
+        }
+        let Ok(html) = cargo::read_doc_html_by_rel_path(&target, &page).await else {
+            // ignore missing module page
+            continue;
+        };
+        let base_dir = std::path::Path::new(&page)
+            .parent()
+            .unwrap_or(std::path::Path::new(""))
+            .to_path_buf();
+
+        let recursive = req.recursive;
+        let crate_name = target.doc_name.clone();
+        let (page_reexports, symbols) = cargo::parse_blocking(move || {
+            let page_reexports = cargo::extract_reexports(&html, &base_dir, &module_path);
+            let symbols = if recursive {
+                cargo::process_page(&html, &base_dir, &crate_name, &[]).0
+            } else {
+                Vec::new()
+            };
+            (page_reexports, symbols)
+        })
+        .await?;
+        reexports.extend(page_reexports);
+
+        if req.recursive {
+            for module in symbols.into_iter().filter(|s| s.symbol_type == "module") {
+                queue.push_back((module.symbol_path, module.fully_qualified_name));
+            }
+        }
+    }
+
+    Ok(Response { reexports })
+}
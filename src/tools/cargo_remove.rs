@@ -0,0 +1,64 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// dependency to remove, as named in the manifest (the rename when it has one)
+    pub name: String,
+    /// remove it from `[dev-dependencies]` (`--dev`) instead of `[dependencies]`
+    #[serde(default)]
+    pub dev: bool,
+    /// workspace member (`name` or `name@version`) whose manifest is edited, defaults to the
+    /// root package
+    pub package: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    #[serde(flatten)]
+    pub change: cargo::ManifestChange,
+}
+
+/// Runs `cargo remove` once the dependency is known to be declared; only registered with
+/// `--allow-mutations`
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let name = req.name.trim();
+    let (kind, section) = if req.dev {
+        (
+            cargo_metadata::DependencyKind::Development,
+            "dev-dependency",
+        )
+    } else {
+        (cargo_metadata::DependencyKind::Normal, "dependency")
+    };
+
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_workspace_member(&metadata, req.package.as_deref())?;
+    // platform specific tables need `--target`, they aren't handled
+    let mut declared: Vec<String> = pkg
+        .dependencies
+        .iter()
+        .filter(|d| d.kind == kind && d.target.is_none())
+        .map(|d| d.rename.clone().unwrap_or_else(|| d.name.clone()))
+        .collect();
+    if !declared.iter().any(|d| d == name) {
+        declared.sort();
+        declared.dedup();
+        return Err(CopilotError::DependencyNotDeclared {
+            name: name.to_string(),
+            section,
+            package: format!("{}@{}", pkg.name, pkg.version),
+            declared,
+        });
+    }
+
+    let mut args = vec![name.to_string()];
+    if req.dev {
+        args.push("--dev".to_string());
+    }
+    let change = cargo::edit_manifest("remove", &args, req.package.as_deref()).await?;
+    Ok(Response { change })
+}
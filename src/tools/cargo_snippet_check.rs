@@ -0,0 +1,221 @@
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+/// Package and target name of the throwaway crate the snippet is compiled in
+const SNIPPET_CRATE: &str = "copilot_snippet";
+
+/// File of the throwaway crate holding the snippet, as diagnostics name it
+const SNIPPET_FILE: &str = "src/main.rs";
+
+/// Numbers the project directories of the checks run by this process
+static NEXT_PROJECT: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// Rust code using the crate; wrapped in `fn main() { ... }` unless it defines `fn main` itself
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// whether `cargo check` succeeded
+    pub success: bool,
+    /// compiler diagnostics for the snippet; lines in `src/main.rs`, rendered or in suggestions,
+    /// are relative to the snippet itself
+    pub diagnostics: Vec<cargo::Diagnostic>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let pkg = cargo::find_package(&metadata, &req.crate_id)?;
+
+    // depend on the exact resolved version (or the local checkout for path/git packages)
+    let dependency = match &pkg.source {
+        Some(source) if source.is_crates_io() => format!("version = \"={}\"", pkg.version),
+        _ => {
+            let dir = pkg
+                .manifest_path
+                .parent()
+                .map_or(pkg.manifest_path.as_str(), |p| p.as_str());
+            format!("path = {}", toml_string(dir))
+        }
+    };
+
+    let root = std::env::temp_dir().join("cargo-copilot").join("snippets");
+    // one project per call so concurrent checks don't overwrite each other's sources, the
+    // target dir is shared between snippets so dependencies are only built once
+    let project = root.join(format!(
+        "snippet-{}-{}-{}",
+        pkg.name,
+        std::process::id(),
+        NEXT_PROJECT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let target_dir = root.join("target");
+    tokio::fs::create_dir_all(project.join("src"))
+        .await
+        .map_err(|e| CopilotError::io("create", &project, e))?;
+
+    let manifest = format!(
+        "[package]\nname = \"{SNIPPET_CRATE}\"\nversion = \"0.0.0\"\nedition = \"2024\"\npublish = false\n\n\
+         [workspace]\n\n[dependencies]\n{} = {{ package = \"{}\", {} }}\n",
+        pkg.name, pkg.name, dependency
+    );
+    write_file(&project.join("Cargo.toml"), &manifest).await?;
+
+    // start from the project's lockfile so the snippet resolves the same versions
+    let lockfile = metadata.workspace_root.join("Cargo.lock");
+    if tokio::fs::try_exists(&lockfile).await.unwrap_or(false) {
+        tokio::fs::copy(&lockfile, project.join("Cargo.lock"))
+            .await
+            .map_err(|e| CopilotError::io("copy", &lockfile, e))?;
+    }
+
+    let (source, line_offset) = if MAIN.is_match(&req.snippet) {
+        (format!("#![allow(unused)]\n{}\n", req.snippet), 1)
+    } else {
+        (
+            format!("#![allow(unused)]\nfn main() {{\n{}\n}}\n", req.snippet),
+            2,
+        )
+    };
+    write_file(&project.join("src").join("main.rs"), &source).await?;
+
+    let result = check(&project, &target_dir, line_offset).await;
+    // best effort, a leftover directory only takes space in the temp dir
+    let _ = tokio::fs::remove_dir_all(&project).await;
+    result
+}
+
+async fn check(
+    project: &std::path::Path,
+    target_dir: &std::path::Path,
+    line_offset: usize,
+) -> Result<Response, CopilotError> {
+    let mut cmd = cargo::cargo_command();
+    cmd.arg("check")
+        .arg("--message-format=json")
+        .arg("--manifest-path")
+        .arg(project.join("Cargo.toml"))
+        .env("CARGO_TARGET_DIR", target_dir);
+    if cargo::offline() {
+        cmd.arg("--offline");
+    }
+    let timeout = cargo::build_timeout();
+    let output = tokio::time::timeout(timeout, cargo::output_in_group(&mut cmd))
+        .await
+        .map_err(|_| CopilotError::CargoCheckTimeout(timeout))?
+        .map_err(|source| CopilotError::Spawn {
+            command: "cargo check",
+            source,
+        })?;
+
+    let mut diagnostics = cargo::parse_diagnostics(&output.stdout, Some(SNIPPET_CRATE));
+    for diag in &mut diagnostics {
+        if diag.file.as_deref() == Some(SNIPPET_FILE)
+            && let Some(line) = diag.line.as_mut()
+        {
+            *line = line.saturating_sub(line_offset);
+        }
+        for suggestion in &mut diag.suggestions {
+            if suggestion.file == SNIPPET_FILE {
+                suggestion.line = suggestion.line.saturating_sub(line_offset);
+                suggestion.end_line = suggestion.end_line.saturating_sub(line_offset);
+            }
+        }
+        if let Some(rendered) = diag.rendered.as_mut() {
+            *rendered = shift_rendered(rendered, line_offset);
+        }
+    }
+
+    if !output.status.success() && diagnostics.is_empty() {
+        return Err(CopilotError::CargoCheckFailed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(Response {
+        success: output.status.success(),
+        diagnostics,
+    })
+}
+
+/// `fn main(` as a whole word, so `fn main_loop()` still gets wrapped
+static MAIN: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\bfn\s+main\s*\(").unwrap());
+static LOCATION: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^(\s*(?:-->|:::) )([^:]+):(\d+):(\d+)").unwrap());
+static GUTTER: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^(\d+)(\s+\|)").unwrap());
+
+/// Renumber the `--> src/main.rs:L:C` locations and the line gutters under them in rustc's
+/// rendering so they match the snippet; spans in other files are left alone
+fn shift_rendered(rendered: &str, line_offset: usize) -> String {
+    let mut out = String::with_capacity(rendered.len());
+    let mut in_snippet = false;
+    for line in rendered.split_inclusive('\n') {
+        if let Some(caps) = LOCATION.captures(line) {
+            in_snippet = &caps[2] == SNIPPET_FILE;
+            if in_snippet {
+                let number = caps[3].parse::<usize>().unwrap_or(0);
+                out.push_str(&format!(
+                    "{}{}:{}:{}",
+                    &caps[1],
+                    &caps[2],
+                    number.saturating_sub(line_offset),
+                    &caps[4]
+                ));
+                out.push_str(&line[caps[0].len()..]);
+                continue;
+            }
+        } else if in_snippet && let Some(caps) = GUTTER.captures(line) {
+            let number = caps[1].parse::<usize>().unwrap_or(0);
+            // keep the gutter width so the code stays aligned with the markers below it
+            out.push_str(&format!(
+                "{:<width$}",
+                number.saturating_sub(line_offset),
+                width = caps[1].len()
+            ));
+            out.push_str(&line[caps[1].len()..]);
+            continue;
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+/// `s` as a TOML string: a literal string when it can be one, a basic string escaped with
+/// TOML's rules otherwise
+fn toml_string(s: &str) -> String {
+    if !s.contains('\'') && !s.chars().any(char::is_control) {
+        return format!("'{}'", s);
+    }
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+async fn write_file(path: &std::path::Path, contents: &str) -> Result<(), CopilotError> {
+    tokio::fs::write(path, contents)
+        .await
+        .map_err(|e| CopilotError::io("write", path, e))
+}
@@ -0,0 +1,101 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// symbol path relative to crate docs, e.g. `struct.Foo` or `de/enum.Error`
+    pub symbol_path: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// resolved `name@version` the docs were built for
+    pub crate_id: String,
+    /// source file relative to the crate's source root, e.g. `de/mod.rs`
+    pub file: String,
+    /// first line of `code` in the file, 1-based
+    pub start_line: usize,
+    /// last line of `code` in the file, inclusive
+    pub end_line: usize,
+    /// the symbol's source, or the whole file when the link has no line range
+    pub code: String,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let rel = cargo::symbol_rel_path(&req.symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(&target, &rel).await?;
+    let href = cargo::parse_blocking(move || cargo::extract_source_link(&html))
+        .await?
+        .ok_or_else(|| CopilotError::SelectorMissing {
+            selector: "a.src",
+            page: rel.clone(),
+        })?;
+
+    // the link is relative to the symbol page, resolve it against the doc root
+    let (src_href, fragment) = href.split_once('#').unwrap_or((&href, ""));
+    let page_dir = std::path::Path::new(&target.doc_name)
+        .join(&rel)
+        .parent()
+        .map(std::path::Path::to_path_buf)
+        .unwrap_or_default();
+    let src_rel = cargo::normalize_rel_path(&page_dir.join(src_href))
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let src_html = cargo::read_doc_root_html(&target, &src_rel).await?;
+    let source = cargo::parse_blocking(move || cargo::extract_source_code(&src_html))
+        .await?
+        .ok_or_else(|| CopilotError::SelectorMissing {
+            selector: "pre.rust",
+            page: src_rel.clone(),
+        })?;
+
+    let lines: Vec<&str> = source.lines().collect();
+    let (start_line, end_line) = line_range(fragment)
+        .map(|(start, end)| (start.max(1), end.min(lines.len())))
+        .filter(|(start, end)| start <= end)
+        .unwrap_or((1, lines.len()));
+    let code = lines
+        .get(start_line.saturating_sub(1)..end_line)
+        .unwrap_or_default()
+        .join("\n");
+
+    // `src/<crate>/de/mod.rs.html` -> `de/mod.rs`
+    let file = src_rel
+        .trim_start_matches("src/")
+        .split_once('/')
+        .map_or(src_rel.as_str(), |(_, file)| file)
+        .trim_end_matches(".html")
+        .to_string();
+
+    Ok(Response {
+        crate_id: target.crate_id,
+        file,
+        start_line,
+        end_line,
+        code,
+    })
+}
+
+/// `12-20` or `12` from a source link fragment
+fn line_range(fragment: &str) -> Option<(usize, usize)> {
+    match fragment.split_once('-') {
+        Some((start, end)) => Some((start.parse().ok()?, end.parse().ok()?)),
+        None => {
+            let line = fragment.parse().ok()?;
+            Some((line, line))
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// only run tests whose name contains this string
+    pub test_filter: Option<String>,
+    /// only test this workspace package (`--package`), the whole workspace otherwise
+    pub package: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TestFailure {
+    /// test name, e.g. `tests::parses_empty` or `src/lib.rs - Foo (line 12)` for doc tests
+    pub name: String,
+    /// captured stdout/stderr including the panic message
+    pub output: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// passed|failed|compile_error|cargo_error
+    pub outcome: String,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub failures: Vec<TestFailure>,
+    /// compiler errors when the tests didn't build
+    pub diagnostics: Vec<cargo::Diagnostic>,
+    /// cargo's stderr when it failed without building or running anything
+    pub cargo_error: Option<String>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    // libtest only emits JSON events on nightly, stable output is parsed from the human format
+    let json = is_nightly().await;
+
+    let mut args = vec!["--no-fail-fast".to_string()];
+    args.extend(cargo::package_args(req.package.as_deref()).await?);
+    args.push("--".to_string());
+    if json {
+        args.extend(["-Z", "unstable-options", "--format", "json"].map(String::from));
+    }
+    if let Some(filter) = &req.test_filter {
+        args.push(filter.clone());
+    }
+
+    let output = cargo::run_json_build("test", &args).await?;
+
+    let diagnostics: Vec<cargo::Diagnostic> = cargo::parse_diagnostics(&output.stdout, None)
+        .into_iter()
+        .filter(|d| d.level == "error")
+        .collect();
+    // test harness output is interleaved with cargo's JSON messages
+    let lines: Vec<String> = cargo_metadata::Message::parse_stream(&output.stdout[..])
+        .filter_map(|m| match m {
+            Ok(cargo_metadata::Message::TextLine(line)) => Some(line),
+            _ => None,
+        })
+        .collect();
+    let results = if json {
+        parse_json_events(&lines)
+    } else {
+        parse_human_output(&lines)
+    };
+
+    let count = |status: Status| results.iter().filter(|r| r.status == status).count();
+    let (total, passed, ignored) = (results.len(), count(Status::Passed), count(Status::Ignored));
+    let failures: Vec<TestFailure> = results
+        .into_iter()
+        .filter(|r| r.status == Status::Failed)
+        .map(|r| TestFailure {
+            name: r.name,
+            output: r.output,
+        })
+        .collect();
+
+    let mut cargo_error = None;
+    let outcome = if !diagnostics.is_empty() {
+        "compile_error"
+    } else if !failures.is_empty() {
+        "failed"
+    } else if !output.status.success() {
+        cargo_error = Some(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        "cargo_error"
+    } else {
+        "passed"
+    };
+
+    Ok(Response {
+        outcome: outcome.to_string(),
+        total,
+        passed,
+        failed: failures.len(),
+        ignored,
+        failures,
+        diagnostics,
+        cargo_error,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+struct TestResult {
+    name: String,
+    status: Status,
+    output: String,
+}
+
+async fn is_nightly() -> bool {
+    tokio::process::Command::new("rustc")
+        .arg("--version")
+        .kill_on_drop(true)
+        .output()
+        .await
+        .is_ok_and(|o| {
+            let version = String::from_utf8_lossy(&o.stdout);
+            version.contains("-nightly") || version.contains("-dev")
+        })
+}
+
+/// `{ "type": "test", "event": "failed", "name": ..., "stdout": ... }` lines of
+/// `--format json`
+fn parse_json_events(lines: &[String]) -> Vec<TestResult> {
+    lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|event| event["type"] == "test")
+        .filter_map(|event| {
+            let status = match event["event"].as_str()? {
+                "ok" => Status::Passed,
+                "failed" | "timeout" => Status::Failed,
+                "ignored" => Status::Ignored,
+                _ => return None,
+            };
+            Some(TestResult {
+                name: event["name"].as_str()?.to_string(),
+                status,
+                output: event["stdout"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `test name ... ok|FAILED|ignored` lines plus the `---- name stdout ----` blocks that follow
+fn parse_human_output(lines: &[String]) -> Vec<TestResult> {
+    let mut results: Vec<TestResult> = Vec::new();
+    let mut capturing: Option<usize> = None;
+
+    for line in lines {
+        if let Some(header) = line
+            .strip_prefix("---- ")
+            .and_then(|l| l.strip_suffix(" ----"))
+        {
+            let name = header
+                .strip_suffix(" stdout")
+                .or_else(|| header.strip_suffix(" stderr"))
+                .unwrap_or(header);
+            capturing = results
+                .iter()
+                .position(|r| r.name == name && r.status == Status::Failed);
+            continue;
+        }
+        if line == "failures:" || line.starts_with("test result:") {
+            capturing = None;
+            continue;
+        }
+        if let Some(i) = capturing {
+            results[i].output.push_str(line);
+            results[i].output.push('\n');
+            continue;
+        }
+
+        let Some((name, status)) = line
+            .strip_prefix("test ")
+            .and_then(|l| l.rsplit_once(" ... "))
+        else {
+            continue;
+        };
+        let status = match status {
+            "ok" => Status::Passed,
+            "FAILED" => Status::Failed,
+            s if s.starts_with("ignored") => Status::Ignored,
+            _ => continue,
+        };
+        results.push(TestResult {
+            name: name.to_string(),
+            status,
+            output: String::new(),
+        });
+    }
+
+    for result in &mut results {
+        result.output = result.output.trim_end().to_string();
+    }
+    results
+}
@@ -0,0 +1,112 @@
+use std::collections::{HashMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`; defaults to the root package
+    pub crate_id: Option<String>,
+    /// levels of dependencies to expand below the starting crate, unlimited when omitted
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TreeNode {
+    #[serde(flatten)]
+    pub info: cargo::CrateInfo,
+    /// kinds of the edge from the parent: normal|dev|build, empty for the starting crate
+    pub kinds: Vec<String>,
+    /// the crate's dependencies are listed at its first occurrence in the tree instead
+    pub deduplicated: bool,
+    pub dependencies: Vec<TreeNode>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    pub tree: TreeNode,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let root = match &req.crate_id {
+        Some(crate_id) => cargo::find_package(&metadata, crate_id)?,
+        None => metadata.root_package().ok_or(CopilotError::NoRootPackage)?,
+    };
+
+    let nodes = cargo::resolve_nodes(&metadata);
+
+    let info = cargo::CrateInfo {
+        crate_id: format!("{}@{}", root.name, root.version),
+        crate_name: root.name.clone(),
+        crate_version: root.version.to_string(),
+        crate_description: root.description.clone(),
+        license: cargo::package_license(root),
+        source: Some(cargo::package_source(root)),
+    };
+    let mut tree = TreeNode {
+        info,
+        kinds: Vec::new(),
+        deduplicated: false,
+        dependencies: Vec::new(),
+    };
+    let mut expanded = HashSet::new();
+    expand(
+        &mut tree,
+        &root.id,
+        0,
+        req.max_depth,
+        &nodes,
+        &metadata,
+        &mut expanded,
+    );
+
+    Ok(Response { tree })
+}
+
+/// Fill in the dependencies of `tree`. Every package is expanded once; later edges to it (shared
+/// transitive deps and dev-dependency cycles) are kept but marked `deduplicated`.
+fn expand<'a>(
+    tree: &mut TreeNode,
+    id: &'a cargo_metadata::PackageId,
+    depth: usize,
+    max_depth: Option<usize>,
+    nodes: &HashMap<&'a cargo_metadata::PackageId, &'a cargo_metadata::Node>,
+    metadata: &cargo_metadata::Metadata,
+    expanded: &mut HashSet<&'a cargo_metadata::PackageId>,
+) {
+    if max_depth.is_some_and(|max| depth >= max) {
+        return;
+    }
+    let Some(node) = nodes.get(id) else {
+        return;
+    };
+    if !expanded.insert(id) {
+        tree.deduplicated = true;
+        return;
+    }
+
+    let mut deps: Vec<&cargo_metadata::NodeDep> = node.deps.iter().collect();
+    deps.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.pkg.cmp(&b.pkg)));
+    for dep in deps {
+        let mut child = TreeNode {
+            info: cargo::format_dep_info(dep, metadata),
+            kinds: cargo::dependency_kinds(dep.dep_kinds.iter().map(|k| k.kind)),
+            deduplicated: false,
+            dependencies: Vec::new(),
+        };
+        expand(
+            &mut child,
+            &dep.pkg,
+            depth + 1,
+            max_depth,
+            nodes,
+            metadata,
+            expanded,
+        );
+        tree.dependencies.push(child);
+    }
+}
@@ -0,0 +1,58 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate id in the form `name@version` or just `name`
+    pub crate_id: String,
+    /// symbol path of a struct, enum or union relative to crate docs, e.g. `struct.Foo`
+    pub symbol_path: String,
+    #[serde(flatten)]
+    pub build: cargo::DocBuildArgs,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// impls written for the type, including derives
+    pub trait_implementations: Vec<cargo::TraitImpl>,
+    /// auto traits (`Send`, `Sync`, `Unpin`, ...) the compiler implements
+    pub auto_trait_implementations: Vec<cargo::TraitImpl>,
+    /// generic impls such as `impl<T> From<T> for T` that apply to every type
+    pub blanket_implementations: Vec<cargo::TraitImpl>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let target = cargo::resolve_doc_target(&req.crate_id)
+        .await?
+        .with_triple(req.build.target.as_deref());
+    cargo::doc_with_options(&target, &req.build.doc_options()).await?;
+
+    let rel = cargo::symbol_rel_path(&req.symbol_path);
+    let html = cargo::read_doc_html_by_rel_path(&target, &rel).await?;
+    let base_dir = std::path::Path::new(&rel)
+        .parent()
+        .unwrap_or(std::path::Path::new(""))
+        .to_path_buf();
+
+    cargo::parse_blocking(move || Response {
+        trait_implementations: cargo::extract_trait_impls(
+            &html,
+            &base_dir,
+            "trait-implementations",
+        ),
+        auto_trait_implementations: cargo::extract_trait_impls(
+            &html,
+            &base_dir,
+            "synthetic-implementations",
+        ),
+        blanket_implementations: cargo::extract_trait_impls(
+            &html,
+            &base_dir,
+            "blanket-implementations",
+        ),
+    })
+    .await
+}
@@ -0,0 +1,127 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// only update this dependency (`--package`, a name or `name@version`), every dependency
+    /// otherwise
+    pub package: Option<String>,
+    /// exact version to update `package` to (`--precise`), requires `package`
+    pub precise: Option<String>,
+    /// report what would change without writing `Cargo.lock` (`--dry-run`)
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// `Cargo.lock` entries that changed, in cargo's order
+    pub changes: Vec<LockChange>,
+    /// whether `Cargo.lock` was left untouched
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LockChange {
+    pub name: String,
+    /// previously locked version, `null` for a newly added package
+    pub from: Option<String>,
+    /// newly locked version, `null` for a removed package
+    pub to: Option<String>,
+}
+
+/// Runs `cargo update`; only registered with `--allow-mutations`
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let mut args = Vec::new();
+    if let Some(package) = req.package.as_deref().map(str::trim) {
+        if package.is_empty() || package.starts_with('-') {
+            return Err(CopilotError::InvalidDependencySpec(package.to_string()));
+        }
+        args.extend(["--package".to_string(), package.to_string()]);
+    }
+    if let Some(precise) = req.precise.as_deref().map(str::trim) {
+        if req.package.is_none() {
+            return Err(CopilotError::PreciseWithoutPackage);
+        }
+        if precise.is_empty() || precise.starts_with('-') {
+            return Err(CopilotError::InvalidPreciseVersion(precise.to_string()));
+        }
+        args.extend(["--precise".to_string(), precise.to_string()]);
+    }
+    if req.dry_run {
+        args.push("--dry-run".to_string());
+    }
+    if cargo::offline() {
+        args.push("--offline".to_string());
+    }
+
+    let mut cmd = cargo::cargo_command();
+    cmd.arg("update").args(&args);
+    let output = cargo::output_in_group(&mut cmd)
+        .await
+        .map_err(|source| CopilotError::Spawn {
+            command: "cargo",
+            source,
+        })?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(CopilotError::CargoEditFailed {
+            subcommand: "update",
+            status: output.status,
+            stderr: stderr.trim().to_string(),
+        });
+    }
+
+    let changes: Vec<LockChange> = stderr.lines().filter_map(parse_change).collect();
+    if !req.dry_run && !changes.is_empty() {
+        tracing::info!(
+            args = ?args,
+            changed = changes.len(),
+            "cargo update modified Cargo.lock"
+        );
+    }
+    Ok(Response {
+        changes,
+        dry_run: req.dry_run,
+    })
+}
+
+/// A `Updating foo v1.0.0 -> v1.0.1`, `Downgrading ...`, `Adding foo v1.0.0` or
+/// `Removing foo v1.0.0` status line; cargo may append notes like `(latest: v2.0.0)`
+fn parse_change(line: &str) -> Option<LockChange> {
+    let mut words = line.split_whitespace();
+    let action = words.next()?;
+    let name = words.next()?.to_string();
+    let version = |word: Option<&str>| {
+        word.and_then(|w| w.strip_prefix('v'))
+            .filter(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+            .map(str::to_string)
+    };
+    let first = version(words.next())?;
+    match action {
+        "Updating" | "Downgrading" => {
+            if words.next()? != "->" {
+                return None;
+            }
+            Some(LockChange {
+                name,
+                from: Some(first),
+                to: Some(version(words.next())?),
+            })
+        }
+        "Adding" => Some(LockChange {
+            name,
+            from: None,
+            to: Some(first),
+        }),
+        "Removing" => Some(LockChange {
+            name,
+            from: Some(first),
+            to: None,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+const DEFAULT_MAX_PATHS: usize = 20;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// dependency to explain, `name` (every version of it) or `name@version`
+    pub crate_id: String,
+    /// stop after this many paths, defaults to 20; shared dependencies can be reached in many
+    /// ways
+    pub max_paths: Option<usize>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// `name@version` of the matching packages in the graph
+    pub targets: Vec<String>,
+    /// dependency chains from the root package (or a member of a virtual workspace) down to a
+    /// target, as `name@version` ids
+    pub paths: Vec<Vec<String>>,
+    /// more paths exist than `max_paths`
+    pub truncated: bool,
+}
+
+/// Like `cargo tree -i`, as the list of paths leading to the crate
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let nodes = cargo::resolve_nodes(&metadata);
+    let (name, version) = match req.crate_id.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (req.crate_id.as_str(), None),
+    };
+    let crate_ids: HashMap<&cargo_metadata::PackageId, String> = metadata
+        .packages
+        .iter()
+        .map(|p| (&p.id, format!("{}@{}", p.name, p.version)))
+        .collect();
+
+    let targets: HashSet<&cargo_metadata::PackageId> = metadata
+        .packages
+        .iter()
+        .filter(|p| p.name == name && version.is_none_or(|v| p.version.to_string() == v))
+        .map(|p| &p.id)
+        .filter(|id| nodes.contains_key(id))
+        .collect();
+    if targets.is_empty() {
+        return Err(CopilotError::NotADependency {
+            crate_id: req.crate_id.clone(),
+            suggestions: cargo::suggest_crate_ids(&metadata, name),
+        });
+    }
+
+    // only walk into packages a target can be reached from, so every branch ends in a path
+    let mut dependents: HashMap<&cargo_metadata::PackageId, Vec<&cargo_metadata::PackageId>> =
+        HashMap::new();
+    for node in nodes.values() {
+        for dep in &node.deps {
+            dependents.entry(&dep.pkg).or_default().push(&node.id);
+        }
+    }
+    let mut leads_to_target = targets.clone();
+    let mut pending: Vec<&cargo_metadata::PackageId> = targets.iter().copied().collect();
+    while let Some(id) = pending.pop() {
+        for &dependent in dependents.get(id).into_iter().flatten() {
+            if leads_to_target.insert(dependent) {
+                pending.push(dependent);
+            }
+        }
+    }
+
+    let mut search = Search {
+        nodes: &nodes,
+        targets: &targets,
+        leads_to_target: &leads_to_target,
+        max_paths: req.max_paths.unwrap_or(DEFAULT_MAX_PATHS),
+        stack: Vec::new(),
+        paths: Vec::new(),
+        truncated: false,
+    };
+    let mut roots = cargo::graph_roots(&metadata);
+    roots.sort();
+    for root in roots {
+        if leads_to_target.contains(root) {
+            search.walk(root);
+        }
+    }
+
+    let crate_id = |id: &cargo_metadata::PackageId| {
+        crate_ids
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.repr.clone())
+    };
+    let mut target_ids: Vec<String> = targets.iter().map(|id| crate_id(id)).collect();
+    target_ids.sort();
+    Ok(Response {
+        targets: target_ids,
+        paths: search
+            .paths
+            .iter()
+            .map(|path| path.iter().map(|id| crate_id(id)).collect())
+            .collect(),
+        truncated: search.truncated,
+    })
+}
+
+struct Search<'a> {
+    nodes: &'a HashMap<&'a cargo_metadata::PackageId, &'a cargo_metadata::Node>,
+    targets: &'a HashSet<&'a cargo_metadata::PackageId>,
+    leads_to_target: &'a HashSet<&'a cargo_metadata::PackageId>,
+    max_paths: usize,
+    /// path from the root to the package being visited
+    stack: Vec<&'a cargo_metadata::PackageId>,
+    paths: Vec<Vec<&'a cargo_metadata::PackageId>>,
+    truncated: bool,
+}
+
+impl<'a> Search<'a> {
+    /// Depth first, skipping packages already on the path (dev-dependency cycles)
+    fn walk(&mut self, id: &'a cargo_metadata::PackageId) {
+        if self.truncated || self.stack.contains(&id) {
+            return;
+        }
+        self.stack.push(id);
+        if self.targets.contains(id) {
+            if self.paths.len() == self.max_paths {
+                self.truncated = true;
+            } else {
+                self.paths.push(self.stack.clone());
+            }
+        } else if let Some(node) = self.nodes.get(id) {
+            let mut deps: Vec<&cargo_metadata::PackageId> = node
+                .deps
+                .iter()
+                .map(|dep| &dep.pkg)
+                .filter(|pkg| self.leads_to_target.contains(pkg))
+                .collect();
+            deps.sort();
+            deps.dedup();
+            for dep in deps {
+                self.walk(dep);
+            }
+        }
+        self.stack.pop();
+    }
+}
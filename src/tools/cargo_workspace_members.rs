@@ -0,0 +1,50 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::cargo;
+use crate::error::CopilotError;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MemberInfo {
+    #[serde(flatten)]
+    pub info: cargo::CrateInfo,
+    /// path to the member's `Cargo.toml`
+    pub manifest_path: String,
+    /// the package of the workspace root manifest, false for every member of a virtual workspace
+    pub is_root: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// directory of the workspace root manifest
+    pub workspace_root: String,
+    pub members: Vec<MemberInfo>,
+}
+
+pub async fn run() -> Result<Response, CopilotError> {
+    let metadata = cargo::get_metadata().await?;
+    let root = metadata.root_package().map(|pkg| &pkg.id);
+
+    let mut members: Vec<MemberInfo> = metadata
+        .workspace_packages()
+        .into_iter()
+        .map(|pkg| MemberInfo {
+            info: cargo::CrateInfo {
+                crate_id: format!("{}@{}", pkg.name, pkg.version),
+                crate_name: pkg.name.clone(),
+                crate_version: pkg.version.to_string(),
+                crate_description: pkg.description.clone(),
+                license: cargo::package_license(pkg),
+                source: Some(cargo::package_source(pkg)),
+            },
+            manifest_path: pkg.manifest_path.to_string(),
+            is_root: root == Some(&pkg.id),
+        })
+        .collect();
+    members.sort_by(|a, b| a.info.crate_id.cmp(&b.info.crate_id));
+
+    Ok(Response {
+        workspace_root: metadata.workspace_root.to_string(),
+        members,
+    })
+}
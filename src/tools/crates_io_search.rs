@@ -0,0 +1,34 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::CopilotError;
+use crate::registry;
+
+const DEFAULT_PER_PAGE: u32 = 10;
+/// largest page crates.io serves
+const MAX_PER_PAGE: u32 = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// search terms matched by crates.io against crate names, keywords and descriptions
+    pub query: String,
+    /// number of results, defaults to 10, at most 100
+    pub per_page: Option<u32>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    /// number of crates matching the query on crates.io
+    pub total: u64,
+    /// most relevant first
+    pub crates: Vec<registry::CrateSummary>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let per_page = req
+        .per_page
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+    let (total, crates) = registry::search(&req.query, per_page).await?;
+    Ok(Response { total, crates })
+}
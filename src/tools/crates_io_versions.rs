@@ -0,0 +1,26 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::CopilotError;
+use crate::registry;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Request {
+    /// crate name as published on crates.io
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    pub name: String,
+    /// highest version first
+    pub versions: Vec<registry::VersionInfo>,
+}
+
+pub async fn run(req: &Request) -> Result<Response, CopilotError> {
+    let versions = registry::versions(&req.name).await?;
+    Ok(Response {
+        name: req.name.clone(),
+        versions,
+    })
+}
@@ -1,4 +1,45 @@
+pub mod cargo_add;
+pub mod cargo_api_fingerprint;
+pub mod cargo_check;
+pub mod cargo_clippy;
+pub mod cargo_crate_meta;
+pub mod cargo_crate_tags;
 pub mod cargo_dependencies;
+pub mod cargo_depgraph_dot;
+pub mod cargo_depgraph_json;
+pub mod cargo_deprecated;
+pub mod cargo_deps_stats;
+pub mod cargo_doc_attributes;
+pub mod cargo_doc_examples;
 pub mod cargo_doc_get;
+pub mod cargo_doc_get_batch;
+pub mod cargo_doc_grep;
 pub mod cargo_doc_index;
+pub mod cargo_doc_list;
 pub mod cargo_doc_overview;
+pub mod cargo_doc_overview_structured;
+pub mod cargo_doc_search;
+pub mod cargo_doc_sections;
+pub mod cargo_doc_variants;
+pub mod cargo_duplicate_deps;
+pub mod cargo_enum_variants;
+pub mod cargo_expand;
+pub mod cargo_features;
+pub mod cargo_no_std;
+pub mod cargo_outdated_check;
+pub mod cargo_package_info;
+pub mod cargo_readme;
+pub mod cargo_reexports;
+pub mod cargo_remove;
+pub mod cargo_snippet_check;
+pub mod cargo_source_get;
+pub mod cargo_struct_fields;
+pub mod cargo_test;
+pub mod cargo_tree;
+pub mod cargo_type_impls;
+pub mod cargo_type_methods;
+pub mod cargo_update;
+pub mod cargo_why;
+pub mod cargo_workspace_members;
+pub mod crates_io_search;
+pub mod crates_io_versions;